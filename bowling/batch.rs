@@ -0,0 +1,245 @@
+use crate::{notation, Delivery, Game, RollError, Rules, T};
+use std::{fmt, thread};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    InvalidInput(String),
+    Rejected(T, RollError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub roll: usize,
+    pub reason: Reason,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, roll {}: ", self.line, self.roll)?;
+        match &self.reason {
+            Reason::InvalidInput(s) => write!(f, "Invalid input! ({s})"),
+            Reason::Rejected(p, e) => write!(f, "{e} ({p})"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Options {
+    pub rules: Rules,
+    // Input lines are scoresheet marks, one roll per mark.
+    pub notation: bool,
+    // Write each game's scoresheet marks instead of its score.
+    pub marks: bool,
+}
+
+// Rejected rolls are skipped, same as the REPL.
+pub fn play_line(
+    line_number: usize,
+    line: &str,
+    opts: &Options,
+) -> (Game, Vec<Diagnostic>) {
+    let mut game = Game::new(opts.rules.clone());
+    let mut diagnostics = vec![];
+    let tokens = if opts.notation {
+        line.matches(|c: char| !c.is_whitespace())
+            .collect::<Vec<_>>()
+    } else {
+        line.split_whitespace().collect()
+    };
+    for (i, s) in tokens.into_iter().enumerate() {
+        let delivery = if opts.notation {
+            s.chars().next().and_then(|c| notation::delivery(c, &game))
+        } else {
+            s.parse().ok().map(Delivery::Pins)
+        };
+        let reason = match delivery {
+            None => Reason::InvalidInput(s.to_string()),
+            Some(d) => match game.deliver(&d) {
+                Ok(()) => continue,
+                Err(e) => match d {
+                    Delivery::Pins(p) => Reason::Rejected(p, e),
+                    _ => Reason::Rejected(0, e),
+                },
+            },
+        };
+        diagnostics.push(Diagnostic {
+            line: line_number,
+            roll: i + 1,
+            reason,
+        });
+    }
+    (game, diagnostics)
+}
+
+pub fn score(input: &str, opts: &Options) -> (String, Vec<Diagnostic>) {
+    score_lines(0, &input.lines().collect::<Vec<_>>(), opts)
+}
+
+// Lines are split into one contiguous chunk per thread, and the chunks are
+// joined back in order.
+pub fn score_parallel(
+    input: &str,
+    threads: usize,
+    opts: &Options,
+) -> (String, Vec<Diagnostic>) {
+    let lines = input.lines().collect::<Vec<_>>();
+    let chunk = lines.len().div_ceil(threads.max(1)).max(1);
+    thread::scope(|s| {
+        let handles = lines
+            .chunks(chunk)
+            .enumerate()
+            .map(|(i, c)| s.spawn(move || score_lines(i * chunk, c, opts)))
+            .collect::<Vec<_>>();
+        let mut ans = String::new();
+        let mut diagnostics = vec![];
+        for h in handles {
+            let (a, d) = h.join().unwrap();
+            ans += &a;
+            diagnostics.extend(d);
+        }
+        (ans, diagnostics)
+    })
+}
+
+fn score_lines(
+    offset: usize,
+    lines: &[&str],
+    opts: &Options,
+) -> (String, Vec<Diagnostic>) {
+    let mut ans = String::new();
+    let mut diagnostics = vec![];
+    for (i, line) in lines.iter().enumerate() {
+        let (game, d) = play_line(offset + i + 1, line, opts);
+        ans += &if opts.marks {
+            game.to_string()
+        } else {
+            game.total().to_string()
+        };
+        ans.push('\n');
+        diagnostics.extend(d);
+    }
+    (ans, diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::batch::*;
+
+    #[test]
+    fn test_score() {
+        let input = "10 10 10 10 10 10 10 10 10 10 10 10\n5 5 x 5\n\n1 10 0";
+        let (ans, diagnostics) = score(input, &Options::default());
+        assert_eq!("300\n15\n0\n1\n", ans);
+        assert_eq!(
+            vec![
+                Diagnostic {
+                    line: 2,
+                    roll: 3,
+                    reason: Reason::InvalidInput("x".to_string()),
+                },
+                Diagnostic {
+                    line: 4,
+                    roll: 2,
+                    reason: Reason::Rejected(
+                        10,
+                        RollError::TooManyPins {
+                            remaining: 9,
+                            attempted: 10
+                        }
+                    ),
+                },
+            ],
+            diagnostics
+        );
+        assert_eq!(
+            "line 4, roll 2: Invalid # of pins! (10)",
+            diagnostics[1].to_string()
+        );
+    }
+
+    #[test]
+    fn test_score_parallel() {
+        let input = (0..100)
+            .map(|i| format!("{} {} 10 {}", i % 11, 10 - i % 11, i % 13))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let opts = &Options::default();
+        for threads in [0, 1, 3, 8, 200] {
+            assert_eq!(
+                score(&input, opts),
+                score_parallel(&input, threads, opts)
+            );
+        }
+        assert_eq!(score("", opts), score_parallel("", 4, opts));
+    }
+
+    #[test]
+    fn test_game_over() {
+        let line = "0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 7";
+        let (_, d) = play_line(1, line, &Options::default());
+        assert_eq!(vec![21], d.iter().map(|d| d.roll).collect::<Vec<_>>());
+        assert_eq!(Reason::Rejected(7, RollError::GameOver), d[0].reason);
+    }
+
+    #[test]
+    fn test_notation() {
+        let input = "X 7/ 9- 81 X X X 9/ -- XXX\n/5 X\n3 2 X 4 9";
+        let opts = Options {
+            notation: true,
+            ..Options::default()
+        };
+        let (ans, diagnostics) = score(input, &opts);
+        assert_eq!("176\n0\n5\n", ans);
+        let d = diagnostics.iter().map(|d| (d.line, d.roll));
+        assert_eq!(vec![(2, 1), (2, 3), (3, 5)], d.collect::<Vec<_>>());
+        assert_eq!(
+            Reason::InvalidInput("/".to_string()),
+            diagnostics[0].reason
+        );
+
+        let opts = Options {
+            marks: true,
+            ..Options::default()
+        };
+        let (ans, _) = score("10 7 3 9 0 8 1 10 10 10 9 1 0 0 10 10 10", &opts);
+        assert_eq!("X 7/ 9- 81 X X X 9/ -- XXX\n", ans);
+    }
+
+    #[test]
+    fn test_rules() {
+        let opts = Options {
+            rules: Rules::five_pin(),
+            ..Options::default()
+        };
+        let (ans, diagnostics) = score(&"15 ".repeat(12), &opts);
+        assert_eq!("450\n", ans);
+        assert!(diagnostics.is_empty());
+
+        let (ans, diagnostics) = score("14 1 2 3 10", &opts);
+        assert_eq!("15\n", ans);
+        let d = diagnostics.iter().map(|d| d.reason.clone());
+        assert_eq!(
+            vec![
+                Reason::Rejected(
+                    14,
+                    RollError::ImpossiblePinfall { attempted: 14 }
+                ),
+                Reason::Rejected(
+                    1,
+                    RollError::ImpossiblePinfall { attempted: 1 }
+                ),
+            ],
+            d.collect::<Vec<_>>()
+        );
+
+        let opts = Options {
+            rules: "scoring=current-frame".parse().unwrap(),
+            notation: true,
+            ..Options::default()
+        };
+        let (ans, diagnostics) = score("XXXXXXXXXXXX\n7/7/", &opts);
+        assert_eq!("300\n34\n", ans);
+        assert_eq!(2, diagnostics.len());
+    }
+}
@@ -0,0 +1,164 @@
+use crate::odds::finishes;
+use crate::Game;
+use std::fmt;
+use std::ops::{Add, Mul};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnumerateError {
+    // More games than a `u128` holds, as with over 20 ten-pin frames.
+    TooManyGames,
+}
+
+impl fmt::Display for EnumerateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EnumerateError::TooManyGames => {
+                write!(f, "Too many games to count!")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EnumerateError {}
+
+// How many distinct games end on each score, indexed by score, counting
+// every way `game` can be finished through `Game::roll`. Games that reach
+// the same state are merged the same way as in `odds::distribution`, with
+// counts in place of chances. A no-tap strike is only counted once, since
+// it is recorded as the whole rack however many pins it took. The counts,
+// and their sum, always fit in a `u128`.
+pub fn enumerate(game: &Game) -> Result<Vec<u128>, EnumerateError> {
+    let counts = finishes(game, Count(Some(1)), |game| {
+        let rack = game.rules().rack();
+        let no_tap = game.rules().no_tap;
+        let fresh = game.fresh_rack();
+        (0..=game.remaining_pins())
+            .filter(move |&k| !fresh || k < no_tap || k == rack)
+            .filter(|k| game.valid(k).is_ok())
+            .map(|k| (k, Count(Some(1))))
+            .collect::<Vec<_>>()
+    });
+    let counts = counts.into_iter().map(|c| c.0).collect::<Option<Vec<_>>>();
+    let counts = counts.ok_or(EnumerateError::TooManyGames)?;
+    let total = counts.iter().try_fold(0u128, |t, &n| t.checked_add(n));
+    total.ok_or(EnumerateError::TooManyGames)?;
+    Ok(counts)
+}
+
+// A count for `finishes`, or `None` once it has overflowed.
+#[derive(Debug, Clone, Copy)]
+struct Count(Option<u128>);
+
+impl Default for Count {
+    fn default() -> Count {
+        Count(Some(0))
+    }
+}
+
+impl Add for Count {
+    type Output = Count;
+
+    fn add(self, other: Count) -> Count {
+        Count(self.0.zip(other.0).and_then(|(a, b)| a.checked_add(b)))
+    }
+}
+
+impl Mul for Count {
+    type Output = Count;
+
+    fn mul(self, other: Count) -> Count {
+        Count(self.0.zip(other.0).and_then(|(a, b)| a.checked_mul(b)))
+    }
+}
+
+pub fn games(counts: &[u128]) -> u128 {
+    counts.iter().sum()
+}
+
+// Over every game equally, not over likely ones.
+pub fn mean(counts: &[u128]) -> f64 {
+    let total = counts.iter().enumerate().map(|(s, &n)| s as f64 * n as f64);
+    total.sum::<f64>() / games(counts).max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::enumerate::*;
+    use crate::{Frame, Rules, T};
+    use std::collections::HashMap;
+
+    // Every pinfall at every roll with no merging, keeping the distinct
+    // finished games.
+    fn brute_force(game: &Game) -> Vec<u128> {
+        fn visit(game: &Game, seen: &mut HashMap<Vec<T>, T>) {
+            if game.is_over() {
+                let rolls = game.frames().iter().flat_map(Frame::rolls);
+                seen.insert(rolls.copied().collect(), game.total());
+                return;
+            }
+            for k in 0..=game.rules().rack() {
+                let mut g = game.clone();
+                if g.roll(&k).is_ok() {
+                    visit(&g, seen);
+                }
+            }
+        }
+        let mut seen = HashMap::new();
+        visit(game, &mut seen);
+        let mut counts = vec![0; game.projection().0 as usize + 1];
+        for &score in seen.values() {
+            counts[score as usize] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn test_ten_pin() {
+        let counts = enumerate(&Game::default()).unwrap();
+        assert_eq!(301, counts.len());
+        assert_eq!(5_726_805_883_325_784_576, games(&counts));
+        // one way each to bowl nothing and everything
+        assert_eq!(1, counts[0]);
+        assert_eq!(1, counts[300]);
+        // eleven strikes, then 9 or 8
+        assert_eq!(1, counts[299]);
+        assert_eq!(1, counts[298]);
+        // a 1 in any of the 20 balls, then a 2 or two 1s
+        assert_eq!(20, counts[1]);
+        assert_eq!(20 + 190, counts[2]);
+    }
+
+    #[test]
+    fn test_brute_force() {
+        let game = "X X X X X X X 9".parse::<Game>().unwrap();
+        assert_eq!(brute_force(&game), enumerate(&game).unwrap());
+
+        // no-tap strikes of 8 and 9 are the same game as a strike
+        let rules = Rules {
+            frames: 2,
+            no_tap: 8,
+            ..Rules::default()
+        };
+        let game = Game::new(rules);
+        assert_eq!(brute_force(&game), enumerate(&game).unwrap());
+    }
+
+    #[test]
+    fn test_overflow() {
+        let frames = |frames| {
+            let rules = Rules {
+                frames,
+                ..Rules::default()
+            };
+            enumerate(&Game::new(rules)).map(|counts| games(&counts))
+        };
+        assert!(frames(20).is_ok());
+        assert_eq!(Err(EnumerateError::TooManyGames), frames(21));
+    }
+
+    #[test]
+    fn test_mean() {
+        assert_eq!(1.0, mean(&[1, 0, 1]));
+        assert_eq!(0.0, mean(&[]));
+    }
+}
@@ -0,0 +1,200 @@
+use crate::lane::Lane;
+use crate::notation;
+
+// A printable scoresheet: one row per bowler, one box per frame with a small
+// box per ball along its top, and the running total underneath. Marks are
+// the ones `Game::frame_to_string` writes.
+
+const MARGIN: usize = 10;
+const NAME: usize = 140;
+const HEADER: usize = 20;
+const BALL: usize = 22;
+const ROW: usize = 60;
+const TOTAL: usize = 60;
+
+const STYLE: &str = "\
+rect{fill:none;stroke:#000;stroke-width:1}\
+text{font-family:sans-serif;font-size:14px;text-anchor:middle}\
+.name{text-anchor:start;font-weight:bold}\
+.header{font-size:11px;fill:#555}\
+.total{font-size:18px}\
+.strike{font-weight:bold;fill:#b00}\
+.spare{font-weight:bold;fill:#00b}";
+
+pub fn svg(lane: &Lane) -> String {
+    let Some(first) = lane.bowlers().first() else {
+        return svg_document(0, 0, "");
+    };
+    let last = lane.rules().last_frame();
+    let widths = (0..=last)
+        .map(|i| first.game().max_rolls(i).max(2) * BALL)
+        .collect::<Vec<_>>();
+    let width = MARGIN * 2 + NAME + widths.iter().sum::<usize>() + TOTAL;
+    let height = MARGIN * 2 + HEADER + ROW * lane.bowlers().len();
+
+    let mut body = String::new();
+    let mut x = MARGIN + NAME;
+    for (i, w) in widths.iter().enumerate() {
+        body += &text(x + w / 2, MARGIN + 14, "header", &(i + 1).to_string());
+        x += w;
+    }
+    body += &text(x + TOTAL / 2, MARGIN + 14, "header", "Total");
+
+    for (row, b) in lane.bowlers().iter().enumerate() {
+        let game = b.game();
+        let y = MARGIN + HEADER + row * ROW;
+        body += &text(MARGIN, y + ROW / 2 + 5, "name", b.name());
+        let mut x = MARGIN + NAME;
+        for (i, &w) in widths.iter().enumerate() {
+            body += &rect(x, y, w, ROW);
+            let marks = game.frame_marks(i);
+            let balls = w / BALL;
+            for j in 0..balls {
+                let bx = x + j * BALL;
+                body += &rect(bx, y, BALL, BALL);
+                if let Some(mark) = marks.get(j) {
+                    body += &text(bx + BALL / 2, y + 16, class(mark), mark);
+                }
+            }
+            if let Some(total) = game.running_total(i) {
+                let t = total.to_string();
+                body += &text(x + w / 2, y + ROW - 12, "total", &t);
+            }
+            x += w;
+        }
+        body += &rect(x, y, TOTAL, ROW);
+        if game.scores().next().is_some() {
+            let t = game.total().to_string();
+            body += &text(x + TOTAL / 2, y + ROW / 2 + 6, "total", &t);
+        }
+    }
+    svg_document(width, height, &body)
+}
+
+// The SVG in a page of its own, ready to print.
+pub fn html(lane: &Lane, title: &str) -> String {
+    let rules = if lane.rules() == &Default::default() {
+        "".to_string()
+    } else {
+        format!("<p>{}</p>\n", escape(&lane.rules().to_string()))
+    };
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n\
+         <style>body{{font-family:sans-serif}}\
+         @media print{{svg{{width:100%;height:auto}}}}</style>\n\
+         </head>\n<body>\n<h1>{title}</h1>\n{rules}{}</body>\n</html>\n",
+        svg(lane),
+        title = escape(title),
+    )
+}
+
+fn svg_document(width: usize, height: usize, body: &str) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" \
+         height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n\
+         <style>{STYLE}</style>\n{body}</svg>\n"
+    )
+}
+
+fn class(mark: &str) -> &'static str {
+    match mark.chars().next() {
+        Some(notation::STRIKE) => "strike",
+        Some(notation::SPARE) => "spare",
+        _ => "mark",
+    }
+}
+
+fn rect(x: usize, y: usize, w: usize, h: usize) -> String {
+    format!("<rect x=\"{x}\" y=\"{y}\" width=\"{w}\" height=\"{h}\"/>\n")
+}
+
+fn text(x: usize, y: usize, class: &str, s: &str) -> String {
+    let s = escape(s);
+    format!("<text x=\"{x}\" y=\"{y}\" class=\"{class}\">{s}</text>\n")
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        match c {
+            '&' => out += "&amp;",
+            '<' => out += "&lt;",
+            '>' => out += "&gt;",
+            '"' => out += "&quot;",
+            '\'' => out += "&#39;",
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::export::*;
+    use crate::Rules;
+
+    fn lane(games: &[(&str, &str)]) -> Lane {
+        let names = games.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        let mut lane = Lane::with_bowlers(&names, Rules::default()).unwrap();
+        for (i, (_, marks)) in games.iter().enumerate() {
+            for c in marks.chars().filter(|c| !c.is_whitespace()) {
+                let game = lane.bowlers()[i].game();
+                let p = notation::pins(c, game).unwrap();
+                lane.roll_bowler(i, &p).unwrap();
+            }
+        }
+        lane
+    }
+
+    fn texts(svg: &str, class: &str) -> Vec<String> {
+        let open = format!("class=\"{class}\">");
+        svg.split(&open)
+            .skip(1)
+            .map(|s| s[..s.find("</text>").unwrap()].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_svg() {
+        let s = svg(&lane(&[
+            ("Alice", "X 7/ 9- 81 X X X 9/ -- XXX"),
+            ("<Bob>", "9"),
+        ]));
+        assert!(s.starts_with("<svg "));
+        assert!(s.contains("width=\"682\" height=\"160\""));
+        assert_eq!(vec!["Alice", "&lt;Bob&gt;"], texts(&s, "name"));
+        let header = texts(&s, "header");
+        assert_eq!(
+            vec!["1", "2", "10", "Total"],
+            [0, 1, 9, 10].map(|i| &header[i])
+        );
+        // every frame's running total, then the game total
+        assert_eq!(
+            vec![
+                "20", "39", "48", "57", "87", "116", "136", "146", "146", "176"
+            ],
+            texts(&s, "total")[..10]
+        );
+        assert_eq!(vec!["176"], texts(&s, "total")[10..]);
+        assert_eq!(7, texts(&s, "strike").len());
+        assert_eq!(vec!["/", "/"], texts(&s, "spare"));
+        assert_eq!(
+            vec!["7", "9", "-", "8", "1", "9", "-", "-", "9"],
+            texts(&s, "mark")
+        );
+        // 2 boxes in each of 9 frames and 3 in the last, plus the total box
+        // and the frame itself, per bowler
+        assert_eq!(2 * (9 * 3 + 4 + 1), s.matches("<rect").count());
+    }
+
+    #[test]
+    fn test_html() {
+        let h = html(&lane(&[("a", "X")]), "League & night");
+        assert!(h.contains("<title>League &amp; night</title>"));
+        assert!(h.contains("<svg "));
+        assert!(!h.contains("frames="));
+        let l = Lane::with_bowlers(&["a"], Rules::candlepin()).unwrap();
+        assert!(html(&l, "").contains("<p>frames=10 balls=3 "));
+    }
+}
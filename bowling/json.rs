@@ -0,0 +1,103 @@
+use crate::lane::Lane;
+use crate::{Frame, Game};
+
+// Just enough JSON to report state to scripts, without a serializer.
+
+pub fn string(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            '\r' => out += "\\r",
+            '\t' => out += "\\t",
+            c if (c as u32) < 0x20 => out += &format!("\\u{:04x}", c as u32),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+pub fn array<I: IntoIterator<Item = String>>(items: I) -> String {
+    format!("[{}]", items.into_iter().collect::<Vec<_>>().join(","))
+}
+
+// `"key":value` pairs, with the values already JSON.
+pub fn object(fields: &[(&str, String)]) -> String {
+    let fields = fields.iter().map(|(k, v)| format!("{}:{v}", string(k)));
+    format!("{{{}}}", fields.collect::<Vec<_>>().join(","))
+}
+
+fn option<T: ToString>(x: Option<T>) -> String {
+    x.map_or("null".to_string(), |x| x.to_string())
+}
+
+// Rolls and marks per frame, and running totals as far as they are known.
+pub fn game(g: &Game) -> String {
+    let frames = g
+        .frames()
+        .iter()
+        .map(|f| array(f.rolls().iter().map(|p| p.to_string())));
+    let marks = (0..g.frames().len()).map(|i| string(&g.frame_to_string(i)));
+    let totals = (0..g.frames().len()).flat_map(|i| g.running_total(i));
+    let leaves = g.frames().iter().map(Frame::leave);
+    let (max, min) = g.projection();
+    object(&[
+        ("frames", array(frames)),
+        ("marks", array(marks)),
+        ("totals", array(totals.map(|t| t.to_string()))),
+        (
+            "leaves",
+            array(leaves.map(|l| option(l.map(|l| string(&l.to_string()))))),
+        ),
+        ("total", g.total().to_string()),
+        ("max", max.to_string()),
+        ("min", min.to_string()),
+        ("over", g.is_over().to_string()),
+    ])
+}
+
+pub fn lane(l: &Lane) -> String {
+    let bowlers = l.bowlers().iter().map(|b| {
+        object(&[("name", string(b.name())), ("game", game(b.game()))])
+    });
+    object(&[
+        ("rules", string(&l.rules().to_string())),
+        ("turn", option(l.turn())),
+        ("over", l.is_over().to_string()),
+        ("bowlers", array(bowlers)),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::*;
+
+    #[test]
+    fn test_string() {
+        assert_eq!(r#""a \"b\" \\ \n\u0001""#, string("a \"b\" \\ \n\u{1}"));
+        assert_eq!(r#""é""#, string("é"));
+    }
+
+    #[test]
+    fn test_game() {
+        let g = "X 7/ 9".parse::<Game>().unwrap();
+        assert_eq!(
+            concat!(
+                r#"{"frames":[[10],[7,3],[9]],"marks":["X","7/","9"],"#,
+                r#""totals":[20,39],"leaves":[null,null,null],"total":39,"#,
+                r#""max":269,"min":48,"over":false}"#
+            ),
+            game(&g)
+        );
+        let l = Lane::new();
+        assert!(lane(&l).starts_with(r#"{"rules":"frames=10 "#));
+        assert!(lane(&l).ends_with(concat!(
+            r#""turn":0,"over":false,"bowlers":[{"name":"","game":"#,
+            r#"{"frames":[[]],"marks":[""],"totals":[],"leaves":[null],"#,
+            r#""total":0,"max":300,"min":0,"over":false}}]}"#
+        )));
+    }
+}
@@ -0,0 +1,398 @@
+use crate::notation::{self, NotationError};
+use crate::{Delivery, Game, Invalidated, Leave, RollError, Rules, T};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaneError {
+    DuplicateName(String),
+}
+
+impl fmt::Display for LaneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LaneError::DuplicateName(s) => write!(f, "Duplicate name! ({s})"),
+        }
+    }
+}
+
+impl std::error::Error for LaneError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bowler {
+    name: String,
+    game: Game,
+}
+
+impl Bowler {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+}
+
+// Bowlers take turns a frame at a time, in the order they were added. The
+// turn is never stored: it belongs to the first bowler with the fewest
+// completed frames, so undoing anyone's roll hands the turn back correctly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lane {
+    rules: Rules,
+    bowlers: Vec<Bowler>,
+    history: Vec<usize>,
+    undone: Vec<usize>,
+}
+
+impl Default for Lane {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Lane {
+    // A single unnamed bowler, renamed by the first call to `add`.
+    pub fn new() -> Lane {
+        Lane::with_bowlers(&[""], Rules::default()).unwrap()
+    }
+
+    // Bowlers in turn order. An empty name is the unnamed bowler.
+    pub fn with_bowlers(
+        names: &[&str],
+        rules: Rules,
+    ) -> Result<Lane, LaneError> {
+        let mut lane = Lane {
+            rules,
+            bowlers: vec![],
+            history: vec![],
+            undone: vec![],
+        };
+        for &name in names {
+            if lane.position(name).is_some() {
+                return Err(LaneError::DuplicateName(name.to_string()));
+            }
+            lane.bowlers.push(Bowler {
+                name: name.to_string(),
+                game: Game::new(lane.rules.clone()),
+            });
+        }
+        Ok(lane)
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn bowlers(&self) -> &[Bowler] {
+        &self.bowlers
+    }
+
+    // Which bowler made each roll, in order.
+    pub fn history(&self) -> &[usize] {
+        &self.history
+    }
+
+    pub fn add(&mut self, name: &str) -> Result<(), LaneError> {
+        if self.position(name).is_some() {
+            return Err(LaneError::DuplicateName(name.to_string()));
+        }
+        if let [b] = &mut self.bowlers[..] {
+            if b.name.is_empty() && self.history.is_empty() {
+                b.name = name.to_string();
+                return Ok(());
+            }
+        }
+        self.bowlers.push(Bowler {
+            name: name.to_string(),
+            game: Game::new(self.rules.clone()),
+        });
+        Ok(())
+    }
+
+    pub fn turn(&self) -> Option<usize> {
+        (0..self.bowlers.len())
+            .filter(|&i| !self.bowlers[i].game.is_over())
+            .min_by_key(|&i| self.bowlers[i].game.frames_completed())
+    }
+
+    pub fn current(&self) -> Option<&Bowler> {
+        self.bowlers.get(self.turn()?)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.turn().is_none()
+    }
+
+    pub fn roll(&mut self, p: &T) -> Result<(), RollError> {
+        let i = self.turn().ok_or(RollError::GameOver)?;
+        self.roll_bowler(i, p)
+    }
+
+    // Rolls for bowler `i` even if it is not their turn.
+    pub fn roll_bowler(&mut self, i: usize, p: &T) -> Result<(), RollError> {
+        self.deliver_bowler(i, &Delivery::Pins(*p))
+    }
+
+    pub fn deliver(&mut self, d: &Delivery) -> Result<(), RollError> {
+        let i = self.turn().ok_or(RollError::GameOver)?;
+        self.deliver_bowler(i, d)
+    }
+
+    pub fn deliver_bowler(
+        &mut self,
+        i: usize,
+        d: &Delivery,
+    ) -> Result<(), RollError> {
+        self.bowlers[i].game.deliver(d)?;
+        self.history.push(i);
+        self.undone.clear();
+        Ok(())
+    }
+
+    pub fn roll_leave(
+        &mut self,
+        p: &T,
+        leave: &Leave,
+    ) -> Result<(), RollError> {
+        let i = self.turn().ok_or(RollError::GameOver)?;
+        self.roll_leave_bowler(i, p, leave)
+    }
+
+    pub fn roll_leave_bowler(
+        &mut self,
+        i: usize,
+        p: &T,
+        leave: &Leave,
+    ) -> Result<(), RollError> {
+        self.bowlers[i].game.roll_leave(p, leave)?;
+        self.history.push(i);
+        self.undone.clear();
+        Ok(())
+    }
+
+    pub fn roll_mark(&mut self, mark: char) -> Result<(), NotationError> {
+        let over = NotationError::Rejected(mark, RollError::GameOver);
+        let game = &self.current().ok_or(over)?.game;
+        let d = notation::delivery(mark, game)
+            .ok_or(NotationError::InvalidMark(mark))?;
+        self.deliver(&d)
+            .map_err(|e| NotationError::Rejected(mark, e))
+    }
+
+    // Undo the last roll on the lane, whoever bowled it.
+    pub fn undo(&mut self) -> Option<()> {
+        self.undo_at(self.history.len().checked_sub(1)?)
+    }
+
+    // Undo the last roll of bowler `i`, leaving everyone else's frames.
+    pub fn undo_bowler(&mut self, i: usize) -> Option<()> {
+        self.undo_at(self.history.iter().rposition(|&b| b == i)?)
+    }
+
+    fn undo_at(&mut self, i: usize) -> Option<()> {
+        let b = self.history.remove(i);
+        self.undone.push(b);
+        self.bowlers[b].game.undo()
+    }
+
+    pub fn redo(&mut self) -> Option<()> {
+        let b = self.undone.pop()?;
+        self.bowlers[b].game.redo()?;
+        self.history.push(b);
+        Some(())
+    }
+
+    // The bowler who rolled last, or the first bowler.
+    pub fn last(&self) -> usize {
+        self.history.last().copied().unwrap_or(0)
+    }
+
+    pub fn edit(
+        &mut self,
+        b: usize,
+        frame: usize,
+        roll: usize,
+        p: &T,
+    ) -> Result<(Vec<Invalidated>, usize), RollError> {
+        let (invalidated, dead_balls) =
+            self.bowlers[b].game.edit(frame, roll, p)?;
+        // one history entry for every delivery the game no longer has
+        for _ in 0..invalidated.len() + dead_balls {
+            let i = self.history.iter().rposition(|&i| i == b).unwrap();
+            self.history.remove(i);
+        }
+        self.undone.clear();
+        Ok((invalidated, dead_balls))
+    }
+
+    pub fn restart(&mut self) {
+        for b in &mut self.bowlers {
+            b.game = Game::new(self.rules.clone());
+        }
+        self.history.clear();
+        self.undone.clear();
+    }
+
+    pub fn print(&self) {
+        let turn = self.turn();
+        for (i, b) in self.bowlers.iter().enumerate() {
+            let marker = if turn == Some(i) { ">" } else { " " };
+            match &b.name[..] {
+                "" => b.game.print(""),
+                name => b.game.print(&format!("{marker} {name}")),
+            }
+        }
+    }
+
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.bowlers.iter().position(|b| b.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lane::*;
+
+    fn lane(names: &[&str]) -> Lane {
+        let mut lane = Lane::new();
+        for name in names {
+            lane.add(name).unwrap();
+        }
+        lane
+    }
+
+    fn names(lane: &Lane) -> Vec<&str> {
+        lane.bowlers().iter().map(Bowler::name).collect()
+    }
+
+    #[test]
+    fn test_add() {
+        let mut l = lane(&["a", "b"]);
+        assert_eq!(vec!["a", "b"], names(&l));
+        assert_eq!(Err(LaneError::DuplicateName("a".to_string())), l.add("a"));
+
+        let mut l = Lane::new();
+        l.roll(&3).unwrap();
+        l.add("a").unwrap();
+        assert_eq!(vec!["", "a"], names(&l));
+    }
+
+    #[test]
+    fn test_rotation() {
+        let mut l = lane(&["a", "b"]);
+        let turns = "X 7/ 9- X XX".chars().filter(|c| *c != ' ').map(|c| {
+            let turn = l.turn().unwrap();
+            l.roll_mark(c).unwrap();
+            turn
+        });
+        assert_eq!(vec![0, 1, 1, 0, 0, 1, 0, 1], turns.collect::<Vec<_>>());
+
+        // tenth frame bonus rolls are bowled before rotating
+        let mut l = lane(&["a", "b"]);
+        for _ in 0..18 * 2 {
+            l.roll(&0).unwrap();
+        }
+        for (p, turn) in [(10, 0), (10, 0), (10, 0), (5, 1), (5, 1), (5, 1)] {
+            assert_eq!(Some(turn), l.turn());
+            l.roll(&p).unwrap();
+        }
+        assert!(l.is_over());
+        assert_eq!(Err(RollError::GameOver), l.roll(&0));
+        assert_eq!(
+            Err(NotationError::Rejected('X', RollError::GameOver)),
+            l.roll_mark('X')
+        );
+        assert_eq!(
+            vec![30, 15],
+            l.bowlers()
+                .iter()
+                .map(|b| b.game().total())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_undo() {
+        let mut l = lane(&["a", "b"]);
+        for p in [3, 4, 10, 5] {
+            l.roll(&p).unwrap();
+        }
+        // a: 3 4 | 5    b: X
+        assert_eq!(Some(()), l.undo_bowler(1));
+        assert_eq!(Some(1), l.turn());
+        assert_eq!(vec![3, 4, 5], rolls(&l, 0));
+        assert_eq!(Vec::<T>::new(), rolls(&l, 1));
+
+        assert_eq!(Some(()), l.undo());
+        assert_eq!(vec![3, 4], rolls(&l, 0));
+        assert_eq!(Some(()), l.undo_bowler(0));
+        assert_eq!(Some(()), l.undo_bowler(0));
+        assert_eq!(None, l.undo_bowler(0));
+        assert_eq!(None, l.undo());
+    }
+
+    #[test]
+    fn test_redo_and_edit() {
+        let mut l = lane(&["a", "b"]);
+        for p in [3, 4, 10, 5] {
+            l.roll(&p).unwrap();
+        }
+        l.undo_bowler(1);
+        l.undo();
+        assert_eq!(Some(()), l.redo());
+        assert_eq!(Some(()), l.redo());
+        assert_eq!(None, l.redo());
+        assert_eq!(vec![3, 4, 5], rolls(&l, 0));
+        assert_eq!(vec![10], rolls(&l, 1));
+
+        // a's first ball was a 9, so a is back in the first frame
+        let (invalidated, _) = l.edit(0, 0, 0, &9).unwrap();
+        let pins = invalidated.iter().map(|x| x.pins).collect::<Vec<_>>();
+        assert_eq!(vec![4, 5], pins);
+        assert_eq!(vec![9], rolls(&l, 0));
+        assert_eq!(Some(0), l.turn());
+        assert_eq!(None, l.redo());
+        assert_eq!(Some(()), l.undo());
+        assert_eq!(Some(()), l.undo());
+        assert_eq!(None, l.undo());
+        assert_eq!(Vec::<T>::new(), rolls(&l, 0));
+        assert_eq!(Vec::<T>::new(), rolls(&l, 1));
+    }
+
+    #[test]
+    fn test_edit_dead_balls() {
+        let mut l = Lane::new();
+        l.roll(&3).unwrap();
+        l.roll(&4).unwrap();
+        l.deliver(&Delivery::DeadBall).unwrap();
+        l.roll(&5).unwrap();
+        let (invalidated, dead_balls) = l.edit(0, 0, 0, &9).unwrap();
+        assert_eq!((2, 1), (invalidated.len(), dead_balls));
+        assert_eq!(1, l.history().len());
+        let saved = crate::save::save(&l);
+        assert_eq!(Ok(l), crate::save::load(&saved));
+    }
+
+    #[test]
+    fn test_leave() {
+        let mut l = lane(&["a", "b"]);
+        let leave = "7-10".parse().unwrap();
+        l.roll_leave(&8, &leave).unwrap();
+        assert_eq!(
+            Err(RollError::InvalidLeave),
+            l.roll_leave_bowler(0, &2, &leave)
+        );
+        l.roll(&2).unwrap();
+        assert_eq!(Some(leave), l.bowlers()[0].game().frames()[0].leave());
+        l.undo();
+        l.undo();
+        assert_eq!(None, l.bowlers()[0].game().frames()[0].leave());
+        l.redo();
+        assert_eq!(Some(leave), l.bowlers()[0].game().frames()[0].leave());
+    }
+
+    fn rolls(l: &Lane, i: usize) -> Vec<T> {
+        let frames = l.bowlers()[i].game().frames();
+        frames.iter().flat_map(|f| f.rolls()).copied().collect()
+    }
+}
@@ -0,0 +1,694 @@
+use crate::lane::Lane;
+use crate::T;
+use std::fmt;
+
+// A season of weekly series between teams. Handicap is `percent` of the
+// difference between `basis` and a bowler's average entering the week, so
+// nobody has one until they have bowled a week. Each game a team wins
+// against its opponent, counting handicap, is worth a point, and so is the
+// series; a tie splits the point. Saved as:
+//
+//     handicap 220 90
+//     games 3
+//     team Pin Pals: Alice, Bob
+//     team Gutter Kings: Carol, Dan
+//     week
+//     match Pin Pals vs Gutter Kings
+//     series 180 200 175 Alice
+//     series 150 140 160 Bob
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LeagueError {
+    InvalidHandicap,
+    InvalidGames,
+    DuplicateTeam(String),
+    DuplicateBowler(String),
+    UnknownTeam(String),
+    UnknownBowler(String),
+    WrongGames(String),
+    Unfinished(String),
+    WeeksRecorded,
+    InvalidName(String),
+}
+
+impl fmt::Display for LeagueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LeagueError::InvalidHandicap => write!(f, "Invalid handicap!"),
+            LeagueError::InvalidGames => write!(f, "Invalid games!"),
+            LeagueError::DuplicateTeam(s) => write!(f, "Duplicate team! ({s})"),
+            LeagueError::DuplicateBowler(s) => {
+                write!(f, "Duplicate bowler! ({s})")
+            }
+            LeagueError::UnknownTeam(s) => write!(f, "Unknown team! ({s})"),
+            LeagueError::UnknownBowler(s) => {
+                write!(f, "Unknown bowler! ({s})")
+            }
+            LeagueError::WrongGames(s) => {
+                write!(f, "Wrong number of games! ({s})")
+            }
+            LeagueError::Unfinished(s) => write!(f, "Unfinished game! ({s})"),
+            LeagueError::WeeksRecorded => {
+                write!(f, "Weeks already recorded!")
+            }
+            LeagueError::InvalidName(s) => write!(f, "Invalid name! ({s})"),
+        }
+    }
+}
+
+impl std::error::Error for LeagueError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    InvalidLine(usize),
+    League(usize, LeagueError),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::InvalidLine(i) => write!(f, "line {i}: Invalid line!"),
+            LoadError::League(i, e) => write!(f, "line {i}: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Team {
+    pub name: String,
+    pub bowlers: Vec<String>,
+}
+
+// Who met whom, by team index, and every bowler's scratch scores.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Week {
+    pub matches: Vec<(usize, usize)>,
+    pub series: Vec<(String, Vec<T>)>,
+}
+
+impl Week {
+    pub fn series(&self, bowler: &str) -> Option<&[T]> {
+        let series = self.series.iter().find(|(name, _)| name == bowler);
+        series.map(|(_, games)| &games[..])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TeamStanding {
+    pub team: usize,
+    pub points: f64,
+    pub scratch: T,
+    pub handicap: T,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BowlerStanding {
+    pub name: String,
+    pub team: usize,
+    pub games: usize,
+    pub pins: T,
+    pub average: Option<T>,
+    pub high_game: T,
+    pub high_series: T,
+    // For the coming week.
+    pub handicap: T,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct League {
+    basis: T,
+    percent: T,
+    games: usize,
+    teams: Vec<Team>,
+    weeks: Vec<Week>,
+}
+
+impl Default for League {
+    fn default() -> Self {
+        League::new(220, 90, 3).unwrap()
+    }
+}
+
+impl League {
+    pub fn new(
+        basis: T,
+        percent: T,
+        games: usize,
+    ) -> Result<League, LeagueError> {
+        if percent > 100 {
+            return Err(LeagueError::InvalidHandicap);
+        }
+        if games == 0 {
+            return Err(LeagueError::InvalidGames);
+        }
+        Ok(League {
+            basis,
+            percent,
+            games,
+            teams: vec![],
+            weeks: vec![],
+        })
+    }
+
+    pub fn teams(&self) -> &[Team] {
+        &self.teams
+    }
+
+    pub fn weeks(&self) -> &[Week] {
+        &self.weeks
+    }
+
+    pub fn games(&self) -> usize {
+        self.games
+    }
+
+    // Only before any weeks, so earlier handicaps stay as they were bowled.
+    pub fn set_handicap(
+        &mut self,
+        basis: T,
+        percent: T,
+    ) -> Result<(), LeagueError> {
+        if !self.weeks.is_empty() {
+            return Err(LeagueError::WeeksRecorded);
+        }
+        if percent > 100 {
+            return Err(LeagueError::InvalidHandicap);
+        }
+        self.basis = basis;
+        self.percent = percent;
+        Ok(())
+    }
+
+    pub fn add_team(
+        &mut self,
+        name: &str,
+        bowlers: &[&str],
+    ) -> Result<(), LeagueError> {
+        for &name in [name].iter().chain(bowlers) {
+            if !valid_name(name) {
+                return Err(LeagueError::InvalidName(name.to_string()));
+            }
+        }
+        if self.team_position(name).is_some() {
+            return Err(LeagueError::DuplicateTeam(name.to_string()));
+        }
+        for (i, &b) in bowlers.iter().enumerate() {
+            if self.team_of(b).is_some() || bowlers[..i].contains(&b) {
+                return Err(LeagueError::DuplicateBowler(b.to_string()));
+            }
+        }
+        self.teams.push(Team {
+            name: name.to_string(),
+            bowlers: bowlers.iter().map(|b| b.to_string()).collect(),
+        });
+        Ok(())
+    }
+
+    pub fn team_position(&self, name: &str) -> Option<usize> {
+        self.teams.iter().position(|t| t.name == name)
+    }
+
+    pub fn team_of(&self, bowler: &str) -> Option<usize> {
+        self.teams
+            .iter()
+            .position(|t| t.bowlers.iter().any(|b| b == bowler))
+    }
+
+    pub fn add_week(&mut self, week: Week) -> Result<(), LeagueError> {
+        let mut seen = vec![];
+        for &(a, b) in &week.matches {
+            for t in [a, b] {
+                let Some(team) = self.teams.get(t) else {
+                    return Err(LeagueError::UnknownTeam(t.to_string()));
+                };
+                if seen.contains(&t) || a == b {
+                    return Err(LeagueError::DuplicateTeam(team.name.clone()));
+                }
+                seen.push(t);
+            }
+        }
+        for (i, (name, games)) in week.series.iter().enumerate() {
+            if self.team_of(name).is_none() {
+                return Err(LeagueError::UnknownBowler(name.clone()));
+            }
+            if week.series[..i].iter().any(|(n, _)| n == name) {
+                return Err(LeagueError::DuplicateBowler(name.clone()));
+            }
+            if games.len() != self.games {
+                return Err(LeagueError::WrongGames(name.clone()));
+            }
+        }
+        self.weeks.push(week);
+        Ok(())
+    }
+
+    // A week from one saved lane per game. Teams sharing a lane bowled
+    // against each other.
+    pub fn week_from_lanes(&self, lanes: &[Lane]) -> Result<Week, LeagueError> {
+        let mut week = Week::default();
+        for lane in lanes {
+            let mut teams = vec![];
+            for b in lane.bowlers() {
+                let name = b.name();
+                let t = self
+                    .team_of(name)
+                    .ok_or_else(|| LeagueError::UnknownBowler(name.into()))?;
+                if !b.game().is_over() {
+                    return Err(LeagueError::Unfinished(name.to_string()));
+                }
+                match week.series.iter_mut().find(|(n, _)| n == name) {
+                    Some((_, games)) => games.push(b.game().total()),
+                    None => week
+                        .series
+                        .push((name.to_string(), vec![b.game().total()])),
+                }
+                if !teams.contains(&t) {
+                    teams.push(t);
+                }
+            }
+            if let [a, b] = teams[..] {
+                let known = |&(x, y): &(usize, usize)| {
+                    (x, y) == (a, b) || (x, y) == (b, a)
+                };
+                if !week.matches.iter().any(known) {
+                    week.matches.push((a, b));
+                }
+            }
+        }
+        for (name, games) in &week.series {
+            if games.len() != self.games {
+                return Err(LeagueError::WrongGames(name.clone()));
+            }
+        }
+        Ok(week)
+    }
+
+    // Scratch pins and games bowled before `week`.
+    fn totals(&self, bowler: &str, week: usize) -> (T, usize) {
+        let series = self.weeks[..week].iter().flat_map(|w| w.series(bowler));
+        series.fold((0, 0), |(pins, games), s| {
+            (pins + s.iter().sum::<T>(), games + s.len())
+        })
+    }
+
+    // Average entering `week`, truncated.
+    pub fn average(&self, bowler: &str, week: usize) -> Option<T> {
+        let (pins, games) = self.totals(bowler, week.min(self.weeks.len()));
+        (games > 0).then(|| pins / games as T)
+    }
+
+    pub fn handicap(&self, bowler: &str, week: usize) -> T {
+        match self.average(bowler, week) {
+            Some(avg) => self.basis.saturating_sub(avg) * self.percent / 100,
+            None => 0,
+        }
+    }
+
+    // Each game's team total with handicap, counting whoever bowled.
+    pub fn team_games(&self, week: usize, team: usize) -> Vec<T> {
+        let mut games = vec![0; self.games];
+        for bowler in &self.teams[team].bowlers {
+            let Some(series) = self.weeks[week].series(bowler) else {
+                continue;
+            };
+            let handicap = self.handicap(bowler, week);
+            for (total, score) in games.iter_mut().zip(series) {
+                *total += score + handicap;
+            }
+        }
+        games
+    }
+
+    // Points won by each side of each match in `week`.
+    pub fn points(&self, week: usize) -> Vec<(f64, f64)> {
+        let point = |a: T, b: T| match a.cmp(&b) {
+            std::cmp::Ordering::Greater => (1.0, 0.0),
+            std::cmp::Ordering::Less => (0.0, 1.0),
+            std::cmp::Ordering::Equal => (0.5, 0.5),
+        };
+        let matches = self.weeks[week].matches.iter();
+        matches
+            .map(|&(a, b)| {
+                let (a, b) =
+                    (self.team_games(week, a), self.team_games(week, b));
+                let series = point(a.iter().sum(), b.iter().sum());
+                a.iter()
+                    .zip(&b)
+                    .map(|(&x, &y)| point(x, y))
+                    .fold(series, |(p, q), (x, y)| (p + x, q + y))
+            })
+            .collect()
+    }
+
+    // Most points first, then most pins with handicap.
+    pub fn standings(&self) -> Vec<TeamStanding> {
+        let mut standings = (0..self.teams.len())
+            .map(|team| TeamStanding {
+                team,
+                points: 0.0,
+                scratch: 0,
+                handicap: 0,
+            })
+            .collect::<Vec<_>>();
+        for (w, week) in self.weeks.iter().enumerate() {
+            for (&(a, b), (p, q)) in week.matches.iter().zip(self.points(w)) {
+                standings[a].points += p;
+                standings[b].points += q;
+            }
+            for (name, series) in &week.series {
+                let s = &mut standings[self.team_of(name).unwrap()];
+                let scratch = series.iter().sum::<T>();
+                s.scratch += scratch;
+                s.handicap +=
+                    scratch + self.handicap(name, w) * series.len() as T;
+            }
+        }
+        standings.sort_by(|a, b| {
+            b.points
+                .total_cmp(&a.points)
+                .then(b.handicap.cmp(&a.handicap))
+        });
+        standings
+    }
+
+    // Every bowler, best average first.
+    pub fn bowlers(&self) -> Vec<BowlerStanding> {
+        let mut bowlers = vec![];
+        for (team, t) in self.teams.iter().enumerate() {
+            for name in &t.bowlers {
+                let (pins, games) = self.totals(name, self.weeks.len());
+                let series = self.weeks.iter().flat_map(|w| w.series(name));
+                let high_series = series.clone().map(|s| s.iter().sum());
+                bowlers.push(BowlerStanding {
+                    name: name.clone(),
+                    team,
+                    games,
+                    pins,
+                    average: self.average(name, self.weeks.len()),
+                    high_game: series.flatten().copied().max().unwrap_or(0),
+                    high_series: high_series.max().unwrap_or(0),
+                    handicap: self.handicap(name, self.weeks.len()),
+                });
+            }
+        }
+        bowlers.sort_by_key(|b| std::cmp::Reverse(b.average));
+        bowlers
+    }
+}
+
+// Names go in the file as they are, so none of its separators, and nothing
+// that loading would trim.
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.trim() == name
+        && !name.contains([':', ',', '\n', '\r'])
+        && !name.contains(" vs ")
+}
+
+pub fn save(league: &League) -> String {
+    let mut s = format!(
+        "handicap {} {}\ngames {}\n",
+        league.basis, league.percent, league.games
+    );
+    for t in &league.teams {
+        s += &format!("team {}: {}\n", t.name, t.bowlers.join(", "));
+    }
+    for week in &league.weeks {
+        s += "week\n";
+        for &(a, b) in &week.matches {
+            let (a, b) = (&league.teams[a].name, &league.teams[b].name);
+            s += &format!("match {a} vs {b}\n");
+        }
+        for (name, games) in &week.series {
+            let games = games.iter().map(|g| g.to_string());
+            s += &format!(
+                "series {} {name}\n",
+                games.collect::<Vec<_>>().join(" ")
+            );
+        }
+    }
+    s
+}
+
+pub fn load(s: &str) -> Result<League, LoadError> {
+    let lines = s.lines().map(|l| l.trim_end_matches('\r'));
+    let mut league = League::default();
+    let mut week: Option<(usize, Week)> = None;
+    for (i, line) in lines.enumerate().filter(|(_, l)| !l.is_empty()) {
+        let i = i + 1;
+        let invalid = LoadError::InvalidLine(i);
+        let err = |e| LoadError::League(i, e);
+        let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let started = week.is_some() || !league.teams.is_empty();
+        match (kind, &mut week) {
+            ("handicap", _) if !started => {
+                let (basis, percent) = rest.split_once(' ').ok_or(invalid)?;
+                let basis =
+                    basis.parse().map_err(|_| LoadError::InvalidLine(i))?;
+                let percent =
+                    percent.parse().map_err(|_| LoadError::InvalidLine(i))?;
+                league =
+                    League::new(basis, percent, league.games).map_err(err)?;
+            }
+            ("games", _) if !started => {
+                let games = rest.parse().map_err(|_| invalid)?;
+                league = League::new(league.basis, league.percent, games)
+                    .map_err(err)?;
+            }
+            ("team", None) => {
+                let (name, bowlers) = rest.split_once(':').ok_or(invalid)?;
+                let bowlers = bowlers
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|b| !b.is_empty())
+                    .collect::<Vec<_>>();
+                league.add_team(name.trim(), &bowlers).map_err(err)?;
+            }
+            ("week", _) if rest.is_empty() => {
+                if let Some((i, w)) = week.take() {
+                    league.add_week(w).map_err(|e| LoadError::League(i, e))?;
+                }
+                week = Some((i, Week::default()));
+            }
+            ("match", Some((_, w))) => {
+                let (a, b) = rest.split_once(" vs ").ok_or(invalid)?;
+                let team = |name: &str| {
+                    league.team_position(name).ok_or_else(|| {
+                        err(LeagueError::UnknownTeam(name.into()))
+                    })
+                };
+                w.matches.push((team(a)?, team(b)?));
+            }
+            ("series", Some((_, w))) => {
+                let mut words = rest.splitn(league.games + 1, ' ');
+                let games = words
+                    .by_ref()
+                    .take(league.games)
+                    .map(|g| g.parse().map_err(|_| LoadError::InvalidLine(i)))
+                    .collect::<Result<Vec<T>, _>>()?;
+                let name = words.next().ok_or(invalid)?;
+                w.series.push((name.to_string(), games));
+            }
+            _ => return Err(invalid),
+        }
+    }
+    if let Some((i, w)) = week {
+        league.add_week(w).map_err(|e| LoadError::League(i, e))?;
+    }
+    Ok(league)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::league::*;
+
+    fn league() -> League {
+        let mut league = League::new(200, 80, 3).unwrap();
+        league.add_team("A", &["a1", "a2"]).unwrap();
+        league.add_team("B", &["b1", "b2"]).unwrap();
+        league
+    }
+
+    fn week(series: &[(&str, [T; 3])]) -> Week {
+        Week {
+            matches: vec![(0, 1)],
+            series: series
+                .iter()
+                .map(|(name, games)| (name.to_string(), games.to_vec()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_handicap() {
+        let mut league = league();
+        league
+            .add_week(week(&[("a1", [150, 160, 171]), ("b1", [200, 210, 220])]))
+            .unwrap();
+        assert_eq!(Some(160), league.average("a1", 1));
+        assert_eq!(None, league.average("a1", 0));
+        assert_eq!(None, league.average("a2", 1));
+        // 80% of 200 - 160
+        assert_eq!(32, league.handicap("a1", 1));
+        assert_eq!(0, league.handicap("b1", 1));
+        assert_eq!(0, league.handicap("a1", 0));
+
+        league
+            .add_week(week(&[("a1", [150, 150, 150]), ("b1", [170, 185, 191])]))
+            .unwrap();
+        // with handicap, a1 wins the first game and ties the series
+        assert_eq!(vec![182, 182, 182], league.team_games(1, 0));
+        assert_eq!(vec![(1.5, 2.5)], league.points(1));
+        assert_eq!(vec![(0.0, 4.0)], league.points(0));
+
+        let standings = league.standings();
+        assert_eq!(1, standings[0].team);
+        assert_eq!(6.5, standings[0].points);
+        assert_eq!(1176, standings[0].scratch);
+        assert_eq!(
+            TeamStanding {
+                team: 0,
+                points: 1.5,
+                scratch: 931,
+                handicap: 1027
+            },
+            standings[1]
+        );
+
+        let bowlers = league.bowlers();
+        assert_eq!(vec!["b1", "a1", "a2", "b2"], {
+            bowlers.iter().map(|b| b.name.as_str()).collect::<Vec<_>>()
+        });
+        assert_eq!(
+            BowlerStanding {
+                name: "a1".to_string(),
+                team: 0,
+                games: 6,
+                pins: 931,
+                average: Some(155),
+                high_game: 171,
+                high_series: 481,
+                handicap: 36
+            },
+            bowlers[1]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut league = league();
+        assert_eq!(
+            Err(LeagueError::DuplicateBowler("a1".to_string())),
+            league.add_team("C", &["c", "a1"])
+        );
+        assert_eq!(
+            Err(LeagueError::DuplicateTeam("A".to_string())),
+            league.add_team("A", &[])
+        );
+        let mut w = week(&[("a1", [1, 2, 3])]);
+        w.series[0].1.pop();
+        assert_eq!(
+            Err(LeagueError::WrongGames("a1".to_string())),
+            league.add_week(w)
+        );
+        assert_eq!(
+            Err(LeagueError::UnknownBowler("c".to_string())),
+            league.add_week(week(&[("c", [1, 2, 3])]))
+        );
+        assert_eq!(Err(LeagueError::InvalidHandicap), League::new(200, 101, 3));
+        assert_eq!(Err(LeagueError::InvalidGames), League::new(200, 90, 0));
+
+        let mut league = League::default();
+        for bad in ["a:b", "a, b", "A vs B", " a", ""] {
+            assert_eq!(
+                Err(LeagueError::InvalidName(bad.to_string())),
+                league.add_team(bad, &[])
+            );
+            assert_eq!(
+                Err(LeagueError::InvalidName(bad.to_string())),
+                league.add_team("A", &["a1", bad])
+            );
+        }
+        assert!(league.teams().is_empty());
+
+        let mut custom = League::default();
+        assert_eq!(
+            Err(LeagueError::InvalidHandicap),
+            custom.set_handicap(200, 101)
+        );
+        custom.set_handicap(200, 80).unwrap();
+        custom.add_team("A", &["a1", "a2"]).unwrap();
+        custom.add_team("B", &["b1", "b2"]).unwrap();
+        assert_eq!(self::league(), custom);
+        custom
+            .add_week(week(&[("a1", [150, 160, 171]), ("b1", [200, 210, 220])]))
+            .unwrap();
+        assert_eq!(
+            Err(LeagueError::WeeksRecorded),
+            custom.set_handicap(220, 90)
+        );
+    }
+
+    #[test]
+    fn test_lanes() {
+        let league = league();
+        let lane = |scores: [(&str, T); 2]| {
+            let names = scores.map(|(name, _)| name);
+            let mut lane =
+                Lane::with_bowlers(&names, Default::default()).unwrap();
+            for (i, (_, p)) in scores.iter().enumerate() {
+                for _ in 0..20 {
+                    lane.roll_bowler(i, p).unwrap();
+                }
+            }
+            lane
+        };
+        let lanes = [1, 2, 3].map(|p| lane([("a1", p), ("b2", 4)]));
+        let week = league.week_from_lanes(&lanes).unwrap();
+        assert_eq!(vec![(0, 1)], week.matches);
+        assert_eq!(Some(&[20, 40, 60][..]), week.series("a1"));
+        assert_eq!(Some(&[80, 80, 80][..]), week.series("b2"));
+        assert_eq!(
+            Err(LeagueError::WrongGames("a1".to_string())),
+            league.week_from_lanes(&lanes[..2])
+        );
+        let mut unfinished = lanes[0].clone();
+        unfinished.undo();
+        assert_eq!(
+            Err(LeagueError::Unfinished("b2".to_string())),
+            league.week_from_lanes(&[unfinished])
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut league = league();
+        league
+            .add_week(week(&[("a1", [150, 160, 171]), ("b1", [200, 210, 220])]))
+            .unwrap();
+        let s = save(&league);
+        assert_eq!(
+            "handicap 200 80\ngames 3\nteam A: a1, a2\nteam B: b1, b2\n\
+             week\nmatch A vs B\nseries 150 160 171 a1\n\
+             series 200 210 220 b1\n",
+            s
+        );
+        assert_eq!(Ok(league), load(&s));
+        assert_eq!(Ok(League::default()), load(""));
+        assert_eq!(
+            Err(LoadError::League(
+                3,
+                LeagueError::UnknownTeam("C".to_string())
+            )),
+            load("team A: a\nweek\nmatch A vs C")
+        );
+        assert_eq!(Err(LoadError::InvalidLine(2)), load("week\nteam A: a"));
+        assert_eq!(
+            Err(LoadError::InvalidLine(3)),
+            load("team A: a\nweek\nseries 100 a")
+        );
+        assert_eq!(Err(LoadError::InvalidLine(2)), load("team A: a\ngames 1"));
+    }
+}
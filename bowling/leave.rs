@@ -0,0 +1,233 @@
+use crate::{Rules, T};
+use std::{fmt, str::FromStr};
+
+// The pins left standing after a first ball, as a set of pin numbers from 1.
+// Splits, washouts and leave names assume a ten-pin rack:
+//
+//     7   8   9   10
+//       4   5   6
+//         2   3
+//           1
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Leave(u32);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LeaveError {
+    InvalidPin(String),
+}
+
+impl fmt::Display for LeaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LeaveError::InvalidPin(s) => write!(f, "Invalid pin! ({s})"),
+        }
+    }
+}
+
+impl std::error::Error for LeaveError {}
+
+// (column, row) of each ten-pin pin, with columns two apart in a row.
+const POSITIONS: [(i32, i32); 10] = [
+    (0, 0),
+    (-1, 1),
+    (1, 1),
+    (-2, 2),
+    (0, 2),
+    (2, 2),
+    (-3, 3),
+    (-1, 3),
+    (1, 3),
+    (3, 3),
+];
+
+const NAMES: [(&[usize], &str); 12] = [
+    (&[7, 10], "bedposts"),
+    (&[4, 6, 7, 10], "big four"),
+    (&[4, 6, 7, 8, 10], "Greek church"),
+    (&[4, 6, 7, 9, 10], "Greek church"),
+    (&[2, 4, 5, 8], "bucket"),
+    (&[3, 5, 6, 9], "bucket"),
+    (&[2, 7], "baby split"),
+    (&[3, 10], "baby split"),
+    (&[5, 10], "dime store"),
+    (&[7, 9], "Cincinnati"),
+    (&[8, 10], "Cincinnati"),
+    (&[2, 8], "sleeper"),
+];
+
+impl Leave {
+    pub const MAX_PINS: usize = 32;
+
+    pub fn new(pins: &[usize]) -> Option<Leave> {
+        let mut leave = Leave::default();
+        for &pin in pins {
+            if !(1..=Leave::MAX_PINS).contains(&pin) {
+                return None;
+            }
+            leave.0 |= 1 << (pin - 1);
+        }
+        Some(leave)
+    }
+
+    pub fn pins(&self) -> impl Iterator<Item = usize> + '_ {
+        (1..=Leave::MAX_PINS).filter(|&pin| self.contains(pin))
+    }
+
+    pub fn contains(&self, pin: usize) -> bool {
+        (1..=Leave::MAX_PINS).contains(&pin) && self.0 & 1 << (pin - 1) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    // Whether every pin is on a rack of these rules.
+    pub fn fits(&self, rules: &Rules) -> bool {
+        self.pins().all(|pin| pin <= rules.pins())
+    }
+
+    // Value of the pins still standing, ignoring any not on the rack.
+    pub fn standing(&self, rules: &Rules) -> T {
+        let values = self.pins().map(|pin| rules.pin_values.get(pin - 1));
+        values.flatten().sum()
+    }
+
+    // Value of the pins the first ball knocked down.
+    pub fn knocked(&self, rules: &Rules) -> T {
+        rules.rack() - self.standing(rules)
+    }
+
+    // The head pin is down and the rest are in separate groups.
+    pub fn is_split(&self) -> bool {
+        !self.contains(1) && self.groups() > 1
+    }
+
+    // A split with the head pin still standing, such as 1-2-10.
+    pub fn is_washout(&self) -> bool {
+        self.contains(1) && self.groups() > 1
+    }
+
+    pub fn name(&self) -> Option<&'static str> {
+        let pins = self.pins().collect::<Vec<_>>();
+        NAMES
+            .iter()
+            .find(|(p, _)| *p == pins)
+            .map(|(_, name)| *name)
+    }
+
+    // Groups of standing pins that touch, or stand one behind the other.
+    // Two pins side by side only count as touching with the pin directly
+    // ahead of them standing too, so 5-6 is a split.
+    fn groups(&self) -> usize {
+        let pins = self.pins().filter(|&p| p <= 10).collect::<Vec<_>>();
+        let touching = |a: usize, b: usize| {
+            let (x, y) = POSITIONS[a - 1];
+            let (u, v) = POSITIONS[b - 1];
+            let ahead = ((x + u) / 2, y - 1);
+            match ((x - u).abs(), (y - v).abs()) {
+                (1, 1) | (0, 2) => true,
+                (2, 0) => pins.iter().any(|&p| POSITIONS[p - 1] == ahead),
+                _ => false,
+            }
+        };
+        let mut group = (0..pins.len()).collect::<Vec<_>>();
+        for i in 0..pins.len() {
+            for j in 0..i {
+                if touching(pins[i], pins[j]) {
+                    let (a, b) = (group[i], group[j]);
+                    group.iter_mut().filter(|g| **g == a).for_each(|g| *g = b);
+                }
+            }
+        }
+        group.sort();
+        group.dedup();
+        group.len()
+    }
+}
+
+// `7-10`, or `X` for a strike.
+impl fmt::Display for Leave {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "X");
+        }
+        let pins = self.pins().map(|pin| pin.to_string());
+        write!(f, "{}", pins.collect::<Vec<_>>().join("-"))
+    }
+}
+
+impl FromStr for Leave {
+    type Err = LeaveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("x") {
+            return Ok(Leave::default());
+        }
+        s.split(['-', ','])
+            .map(|p| p.parse().ok())
+            .collect::<Option<Vec<_>>>()
+            .and_then(|pins| Leave::new(&pins))
+            .ok_or_else(|| LeaveError::InvalidPin(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::leave::*;
+
+    fn leave(s: &str) -> Leave {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(vec![7, 10], leave("7-10").pins().collect::<Vec<_>>());
+        assert_eq!(leave("10-7"), leave("7,10"));
+        assert_eq!("4-6-7-10", leave("10-4-7-6").to_string());
+        assert_eq!("X", leave("x").to_string());
+        assert!(leave("X").is_empty());
+        let err = |s: &str| Err(LeaveError::InvalidPin(s.to_string()));
+        assert_eq!(err("7-"), "7-".parse::<Leave>());
+        assert_eq!(err("0"), "0".parse::<Leave>());
+        assert_eq!(err("33"), "33".parse::<Leave>());
+        assert_eq!(err(""), "".parse::<Leave>());
+    }
+
+    #[test]
+    fn test_pins() {
+        let rules = Rules::ten_pin();
+        assert_eq!(8, leave("7-10").knocked(&rules));
+        assert!(leave("7-10").fits(&rules));
+        assert!(!leave("11").fits(&rules));
+
+        let rules = Rules::five_pin();
+        assert_eq!(5, leave("1-2").standing(&rules));
+        assert_eq!(10, leave("1-2").knocked(&rules));
+    }
+
+    #[test]
+    fn test_splits() {
+        for s in ["7-10", "4-6", "5-7", "3-10", "2-7", "4-6-7-10", "8-10"] {
+            assert!(leave(s).is_split(), "{s}");
+        }
+        // a pin down just ahead of two standing side by side
+        for s in ["5-6", "4-5", "2-3", "7-8"] {
+            assert!(leave(s).is_split(), "{s}");
+        }
+        for s in ["X", "10", "1-2-4", "2-4-5", "4-5-8", "2-8", "6-10"] {
+            assert!(!leave(s).is_split(), "{s}");
+        }
+        assert!(!leave("3-5-6-9").is_split());
+        assert!(leave("1-2-10").is_washout());
+        assert!(leave("1-2-4-10").is_washout());
+        assert!(!leave("1-2-4").is_washout());
+        assert!(!leave("7-10").is_washout());
+        assert_eq!(Some("bedposts"), leave("7-10").name());
+        assert_eq!(Some("Greek church"), leave("4-6-7-9-10").name());
+        assert_eq!(None, leave("1-2").name());
+    }
+}
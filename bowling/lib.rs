@@ -0,0 +1,1120 @@
+pub mod batch;
+pub mod enumerate;
+pub mod export;
+pub mod json;
+pub mod lane;
+pub mod league;
+pub mod leave;
+pub mod notation;
+pub mod odds;
+pub mod reference;
+pub mod rules;
+pub mod save;
+pub mod simulate;
+pub mod stats;
+pub mod tournament;
+
+pub use leave::Leave;
+pub use rules::{Rules, Scoring};
+
+use std::fmt;
+use CompletionStatus::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionStatus {
+    NoBonus,
+    Strike,
+    Spare,
+    Incomplete,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollError {
+    GameOver,
+    TooManyPins { remaining: T, attempted: T },
+    ImpossiblePinfall { attempted: T },
+    NoSuchRoll { frame: usize, roll: usize },
+    InvalidLeave,
+    LeaveMismatch { knocked: T, attempted: T },
+}
+
+impl fmt::Display for RollError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RollError::GameOver => write!(f, "Game Over!"),
+            RollError::TooManyPins { .. } => write!(f, "Invalid # of pins!"),
+            RollError::ImpossiblePinfall { .. } => {
+                write!(f, "Impossible pinfall!")
+            }
+            RollError::NoSuchRoll { .. } => write!(f, "No such roll!"),
+            RollError::InvalidLeave => write!(f, "Invalid leave!"),
+            RollError::LeaveMismatch { .. } => {
+                write!(f, "Pins don't match the leave!")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RollError {}
+
+pub type T = u32;
+
+// A later roll that no longer fits after an `edit`, and was dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Invalidated {
+    pub frame: usize,
+    pub roll: usize,
+    pub pins: T,
+    pub error: RollError,
+}
+
+// What came of a delivery. A foul counts as a ball that knocked down
+// nothing. A dead ball doesn't count at all: the pins are put back as they
+// were and the ball is bowled again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    Pins(T),
+    Foul,
+    DeadBall,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Frame {
+    rolls: Vec<T>,
+    leave: Option<Leave>,
+    fouls: Vec<usize>,
+    dead_balls: Vec<usize>,
+}
+
+impl Frame {
+    pub fn rolls(&self) -> &[T] {
+        &self.rolls
+    }
+
+    // Pins standing after the first ball, if they were entered.
+    pub fn leave(&self) -> Option<Leave> {
+        self.leave
+    }
+
+    pub fn is_foul(&self, j: usize) -> bool {
+        self.fouls.contains(&j)
+    }
+
+    pub fn fouls(&self) -> usize {
+        self.fouls.len()
+    }
+
+    // For each dead ball, the roll it was bowled before.
+    pub fn dead_balls(&self) -> &[usize] {
+        &self.dead_balls
+    }
+
+    pub fn len(&self) -> usize {
+        self.rolls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rolls.is_empty()
+    }
+
+    pub fn sum(&self) -> T {
+        self.rolls.iter().sum()
+    }
+}
+
+// Frames are scored in order, so `totals` holds the running totals of a
+// prefix of `frames`. Everything after it is waiting on rolls or bonuses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    rules: Rules,
+    frames: Vec<Frame>,
+    totals: Vec<T>,
+    undone: Vec<(Delivery, Option<Leave>)>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new(Rules::default())
+    }
+}
+
+impl Game {
+    pub fn new(rules: Rules) -> Game {
+        Game {
+            rules,
+            frames: vec![Frame::default()],
+            totals: vec![],
+            undone: vec![],
+        }
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    pub fn roll(&mut self, p: &T) -> Result<(), RollError> {
+        self.deliver(&Delivery::Pins(*p))
+    }
+
+    pub fn deliver(&mut self, d: &Delivery) -> Result<(), RollError> {
+        match d {
+            Delivery::Pins(p) => self.push(p, None, false)?,
+            Delivery::Foul => self.push(&0, None, true)?,
+            Delivery::DeadBall => self.dead_ball()?,
+        }
+        self.undone.clear();
+        Ok(())
+    }
+
+    fn dead_ball(&mut self) -> Result<(), RollError> {
+        if self.is_over() {
+            return Err(RollError::GameOver);
+        }
+        let f = self.frames.last_mut().unwrap();
+        f.dead_balls.push(f.rolls.len());
+        Ok(())
+    }
+
+    // The first ball of a frame, along with the pins it left standing.
+    pub fn roll_leave(
+        &mut self,
+        p: &T,
+        leave: &Leave,
+    ) -> Result<(), RollError> {
+        self.valid(p)?;
+        let i = self.frames.len() - 1;
+        if !self.frames[i].is_empty() || !leave.fits(&self.rules) {
+            return Err(RollError::InvalidLeave);
+        }
+        let knocked = leave.knocked(&self.rules);
+        if *p != knocked {
+            return Err(RollError::LeaveMismatch {
+                knocked,
+                attempted: *p,
+            });
+        }
+        self.push(p, Some(*leave), false)?;
+        self.undone.clear();
+        Ok(())
+    }
+
+    fn push(
+        &mut self,
+        p: &T,
+        leave: Option<Leave>,
+        foul: bool,
+    ) -> Result<(), RollError> {
+        self.valid(p)?;
+        let i = self.frames.len() - 1;
+        if leave.is_some() && !self.frames[i].is_empty() {
+            return Err(RollError::InvalidLeave);
+        }
+        // a no-tap strike is recorded as the whole rack
+        let p = if self.fresh_rack() && *p >= self.rules.no_tap {
+            self.rules.rack()
+        } else {
+            *p
+        };
+        let f = &mut self.frames[i];
+        if foul {
+            f.fouls.push(f.rolls.len());
+        }
+        f.rolls.push(p);
+        self.frames[i].leave = self.frames[i].leave.or(leave);
+        while let Some(score) = self.frame_score(self.totals.len()) {
+            self.totals.push(self.total() + score);
+        }
+        let last = self.rules.last_frame();
+        if i < last && self.completion_status(i) != Incomplete {
+            self.frames.push(Frame::default());
+        }
+        Ok(())
+    }
+
+    pub fn score(&self, i: usize) -> Option<T> {
+        let t = *self.totals.get(i)?;
+        Some(t - i.checked_sub(1).map_or(0, |j| self.totals[j]))
+    }
+
+    pub fn scores(&self) -> impl Iterator<Item = T> + '_ {
+        (0..self.totals.len()).flat_map(|i| self.score(i))
+    }
+
+    pub fn running_total(&self, i: usize) -> Option<T> {
+        self.totals.get(i).copied()
+    }
+
+    pub fn total(&self) -> T {
+        self.totals.last().copied().unwrap_or(0)
+    }
+
+    // Frames waiting on bonus rolls.
+    pub fn pending(&self) -> impl Iterator<Item = usize> + '_ {
+        (self.totals.len()..self.frames.len())
+            .filter(|&i| self.completion_status(i) != Incomplete)
+    }
+
+    // Highest and lowest final score still reachable: clear every rack from
+    // here on, or roll nothing but gutters.
+    pub fn projection(&self) -> (T, T) {
+        let max = self.play_out(usize::MAX).0;
+        let min = self.play_out(0).0;
+        (max.total(), min.total())
+    }
+
+    // The fewest rack-clearing balls, from the next roll on, that reach
+    // `target` when every ball after them is a gutter. Empty if the target is
+    // already certain, `None` if it is out of reach.
+    pub fn needed(&self, target: T) -> Option<Vec<T>> {
+        for clears in 0.. {
+            let (game, rolls) = self.play_out(clears);
+            if game.total() >= target {
+                return Some(rolls);
+            }
+            if rolls.len() < clears {
+                return None;
+            }
+        }
+        unreachable!()
+    }
+
+    // Finishes the game with `clears` rack-clearing balls then gutters, and
+    // returns it with the clearing balls.
+    fn play_out(&self, clears: usize) -> (Game, Vec<T>) {
+        let mut game = self.clone();
+        let mut rolls = vec![];
+        while !game.is_over() {
+            let p = if rolls.len() < clears {
+                game.remaining_pins()
+            } else {
+                0
+            };
+            game.push(&p, None, false).unwrap();
+            if rolls.len() < clears {
+                rolls.push(p);
+            }
+        }
+        (game, rolls)
+    }
+
+    fn frame_score(&self, i: usize) -> Option<T> {
+        let f = self.frames.get(i)?;
+        let rack = self.rules.rack();
+        let mut lookahead = self.frames[i + 1..].iter().flat_map(Frame::rolls);
+        let mut bonus = |n| (0..n).map(|_| lookahead.next()).sum::<Option<T>>();
+        if self.rules.scoring == Scoring::CurrentFrame {
+            return match self.mark(i) {
+                Incomplete => None,
+                Strike => Some(3 * rack),
+                Spare => Some(rack + f.rolls()[0]),
+                NoBonus => Some(f.sum()),
+            };
+        }
+        if i == self.rules.last_frame() {
+            // bonus balls are part of the frame
+            let rolls = f.rolls();
+            return match (self.completion_status(i), self.mark(i)) {
+                (Incomplete, _) => None,
+                (_, Strike) => Some(rack + rolls[1..].iter().sum::<T>()),
+                (_, Spare) => Some(rack + rolls[2..].iter().sum::<T>()),
+                _ => Some(f.sum()),
+            };
+        }
+        match self.completion_status(i) {
+            Incomplete => None,
+            Strike => Some(rack + bonus(self.rules.strike_bonus)?),
+            Spare => Some(rack + bonus(self.rules.spare_bonus)?),
+            NoBonus => Some(f.sum()),
+        }
+    }
+
+    pub fn valid(&self, p: &T) -> Result<(), RollError> {
+        let remaining_pins = self.remaining_pins();
+        if self.is_over() {
+            Err(RollError::GameOver)
+        } else if p > &remaining_pins {
+            Err(RollError::TooManyPins {
+                remaining: remaining_pins,
+                attempted: *p,
+            })
+        } else if !self.rules.reachable(*p) {
+            Err(RollError::ImpossiblePinfall { attempted: *p })
+        } else {
+            Ok(())
+        }
+    }
+
+    // Standing pin value and balls thrown at the current rack of frame `i`.
+    fn rack(&self, i: usize) -> (T, usize) {
+        let rack = self.rules.rack();
+        let mut standing = rack;
+        let mut balls = 0;
+        for &p in self.frames[i].rolls() {
+            standing -= p;
+            balls += 1;
+            if standing == 0 || (balls == 1 && p >= self.rules.no_tap) {
+                standing = rack;
+                balls = 0;
+            }
+        }
+        (standing, balls)
+    }
+
+    pub fn remaining_pins(&self) -> T {
+        self.rack(self.frames.len() - 1).0
+    }
+
+    // Whether the next roll is the first ball at a full rack.
+    pub fn fresh_rack(&self) -> bool {
+        self.rack(self.frames.len() - 1).1 == 0
+    }
+
+    pub fn frames_completed(&self) -> usize {
+        self.frames.len() - 1 + self.is_over() as usize
+    }
+
+    pub fn is_over(&self) -> bool {
+        let i = self.frames.len() - 1;
+        i == self.rules.last_frame() && self.completion_status(i) != Incomplete
+    }
+
+    pub fn completion_status(&self, i: usize) -> CompletionStatus {
+        let bonus_balls = self.rules.scoring == Scoring::Traditional;
+        if i == self.rules.last_frame() && bonus_balls {
+            let len = self.frames[i].len();
+            let size = match self.mark(i) {
+                Strike => 1 + self.rules.strike_bonus,
+                Spare => 2 + self.rules.spare_bonus,
+                _ => self.rules.balls,
+            };
+            return if len >= size { NoBonus } else { Incomplete };
+        }
+        self.mark(i)
+    }
+
+    // How the first rack of frame `i` went, so the last frame is a strike or
+    // spare here even though it never waits on a bonus.
+    pub fn mark(&self, i: usize) -> CompletionStatus {
+        let rolls = self.frames[i].rolls();
+        let rack = self.rules.rack();
+        match rolls {
+            [] => Incomplete,
+            [first, ..] if *first >= self.rules.no_tap => Strike,
+            [first, second, ..] if first + second == rack => Spare,
+            _ if rolls.len() >= self.rules.balls => NoBonus,
+            _ if rolls.iter().sum::<T>() == rack => NoBonus,
+            _ => Incomplete,
+        }
+    }
+
+    pub fn undo(&mut self) -> Option<()> {
+        let f = self.frames.last_mut()?;
+        if f.dead_balls.last() == Some(&f.len()) {
+            f.dead_balls.pop();
+            self.undone.push((Delivery::DeadBall, None));
+            Some(())
+        } else if self.frames[0].is_empty() {
+            None
+        } else {
+            if self.frames.last()?.is_empty() {
+                self.frames.pop();
+            }
+            let f = self.frames.last_mut()?;
+            let p = f.rolls.pop()?;
+            let foul = f.fouls.last() == Some(&f.len());
+            if foul {
+                f.fouls.pop();
+            }
+            let leave = if f.is_empty() { f.leave.take() } else { None };
+            while let Some(i) = self.totals.len().checked_sub(1) {
+                if self.frame_score(i).is_some() {
+                    break;
+                }
+                self.totals.pop();
+            }
+            let d = if foul {
+                Delivery::Foul
+            } else {
+                Delivery::Pins(p)
+            };
+            self.undone.push((d, leave));
+            Some(())
+        }
+    }
+
+    pub fn redo(&mut self) -> Option<()> {
+        match self.undone.pop()? {
+            (Delivery::Pins(p), leave) => self.push(&p, leave, false).ok(),
+            (Delivery::Foul, _) => self.push(&0, None, true).ok(),
+            (Delivery::DeadBall, _) => self.dead_ball().ok(),
+        }
+    }
+
+    // Replaces a past roll and replays the rest of the game after it. Rolls
+    // that no longer fit are dropped and returned, along with how many dead
+    // balls went with them; the game is left as is if the new roll itself
+    // does not fit. The new roll has no leave and is no foul, and later
+    // leaves are kept only while they still start a frame. Fouls stay with
+    // their rolls, and dead balls with their frames while those last.
+    pub fn edit(
+        &mut self,
+        frame: usize,
+        roll: usize,
+        p: &T,
+    ) -> Result<(Vec<Invalidated>, usize), RollError> {
+        if self.frames.get(frame).is_none_or(|f| roll >= f.len()) {
+            return Err(RollError::NoSuchRoll { frame, roll });
+        }
+        let rolls = self.frames.iter().enumerate().flat_map(|(i, f)| {
+            let leave = move |j| f.leave.filter(|_| j == 0);
+            f.rolls()
+                .iter()
+                .enumerate()
+                .map(move |(j, &q)| (i, j, q, leave(j), f.is_foul(j)))
+        });
+        let mut game = Game::new(self.rules.clone());
+        let mut invalidated = vec![];
+        for (i, j, q, leave, foul) in rolls {
+            let starts_frame = game.frames.last().is_some_and(Frame::is_empty);
+            let leave = leave.filter(|_| starts_frame);
+            if (i, j) < (frame, roll) {
+                game.push(&q, leave, foul).unwrap();
+            } else if (i, j) == (frame, roll) {
+                game.push(p, None, false)?;
+            } else if let Err(error) = game.push(&q, leave, foul) {
+                invalidated.push(Invalidated {
+                    frame: i,
+                    roll: j,
+                    pins: q,
+                    error,
+                });
+            }
+        }
+        // A dead ball after a frame's last roll only stays if the frame is
+        // still being bowled; otherwise it would belong to the next frame.
+        let current = game.frames.len() - 1;
+        let open = !game.is_over();
+        let mut dead_balls = 0;
+        for (i, f) in self.frames.iter().enumerate() {
+            let Some(g) = game.frames.get_mut(i) else {
+                dead_balls += f.dead_balls.len();
+                continue;
+            };
+            let n = g.len();
+            let fits = |&j: &usize| j < n || j == n && open && i == current;
+            let kept = f.dead_balls.iter().copied().filter(fits);
+            let kept = kept.collect::<Vec<_>>();
+            dead_balls += f.dead_balls.len() - kept.len();
+            g.dead_balls = kept;
+        }
+        *self = game;
+        Ok((invalidated, dead_balls))
+    }
+
+    // `name` is printed on its own line above the frames, if not empty.
+    pub fn print(&self, name: &str) {
+        if !name.is_empty() {
+            println!("{name}");
+        }
+        let last = self.rules.last_frame();
+        for i in 0..=last {
+            if i < last {
+                print!("{}:[{:^3}]  ", i + 1, self.frame_to_string(i));
+            } else {
+                println!("{}:[{:5}]", i + 1, self.frame_to_string(i));
+            }
+        }
+        for i in 0..=last {
+            let score = match self.running_total(i) {
+                Some(total) => total.to_string(),
+                None => "".to_string(),
+            };
+            if i < last {
+                print!("  [{:>3}]  ", score);
+            } else {
+                println!("   [{:>5}]", score);
+            }
+        }
+        if !self.is_over() {
+            let (max, min) = self.projection();
+            println!("  max {max}  min {min}");
+        }
+    }
+
+    // Standard scoresheet marks: `X` strike, `/` spare, `-` gutter, `F` foul.
+    pub fn frame_to_string(&self, i: usize) -> String {
+        self.frame_marks(i).concat()
+    }
+
+    // The marks of `frame_to_string`, one per roll.
+    pub fn frame_marks(&self, i: usize) -> Vec<String> {
+        let Some(f) = self.frames.get(i) else {
+            return vec![];
+        };
+        let rack = self.rules.rack();
+        let mut standing = rack;
+        let mut balls = 0;
+        let mut marks = vec![];
+        for (j, &p) in f.rolls().iter().enumerate() {
+            balls += 1;
+            marks.push(if f.is_foul(j) {
+                notation::FOUL.to_string()
+            } else if balls == 1 && p >= self.rules.no_tap {
+                notation::STRIKE.to_string()
+            } else if balls == 2 && p == standing {
+                notation::SPARE.to_string()
+            } else if p == 0 {
+                notation::GUTTER.to_string()
+            } else {
+                p.to_string()
+            });
+            standing -= p;
+            if standing == 0 || (balls == 1 && p >= self.rules.no_tap) {
+                standing = rack;
+                balls = 0;
+            }
+        }
+        marks
+    }
+
+    // Most rolls frame `i` can take, for drawing a box per ball.
+    pub fn max_rolls(&self, i: usize) -> usize {
+        let rules = &self.rules;
+        if i == rules.last_frame() && rules.scoring == Scoring::Traditional {
+            rules
+                .balls
+                .max(1 + rules.strike_bonus)
+                .max(2 + rules.spare_bonus)
+        } else {
+            rules.balls
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rng::Rng;
+
+    const LAST_FRAME: usize = 9;
+
+    fn mock(rolls: &[T]) -> Game {
+        let mut g = Game::default();
+        for r in rolls {
+            let _ = g.roll(r);
+        }
+        g
+    }
+
+    fn score(rolls: &[T]) -> T {
+        mock(rolls).scores().sum()
+    }
+
+    fn mock_str(rolls: &str) -> Game {
+        let mut g = Game::default();
+        for s in rolls.split_whitespace() {
+            let r = s.parse().unwrap();
+            let _ = g.roll(&r);
+        }
+        g
+    }
+
+    fn score_str(rolls: &str) -> T {
+        mock_str(rolls).scores().sum()
+    }
+
+    fn pad(last_frame: &[T]) -> Game {
+        let mut g = mock(&[0; LAST_FRAME * 2]);
+        for p in last_frame {
+            let _ = g.roll(p);
+        }
+        g
+    }
+
+    #[test]
+    fn test_streaks() {
+        assert_eq!(0, score(&[0; 20 + 1]));
+        assert_eq!(20, score(&[1; 20 + 1]));
+        assert_eq!(40, score(&[2; 20 + 1]));
+        assert_eq!(60, score(&[3; 20 + 1]));
+        assert_eq!(80, score(&[4; 20 + 1]));
+        assert_eq!(150, score(&[5; 21 + 1]));
+        assert_eq!(300, score(&[10; 12 + 1]));
+    }
+
+    #[test]
+    fn test_open_frames() {
+        // frame 1     2     3     4     5     6     7     8     9     10
+        let a = "4  5  5  4  5  4  5  4  5  4  5  4  5  4  5  4  5  4  5  4";
+        let b = "1  2  3  4  5  4  3  2  1  0  9  0  0  9  9  0  0  1  0  0";
+        let c = "0  2  0  2  0  2  0  2  0  2  0  2  0  2  0  2  0  2  0  2";
+        let d = "3  0  3  0  3  0  3  0  3  0  3  0  3  0  3  0  3  0  3  0";
+        assert_eq!(90, score_str(a));
+        assert_eq!(53, score_str(b));
+        assert_eq!(20, score_str(c));
+        assert_eq!(30, score_str(d));
+    }
+
+    #[test]
+    fn test_spares() {
+        // frame 1     2     3     4     5     6     7     8     9     10
+        let a = "6  4  5  5  6  4  7  3  1  9  2  8  6  4  5  5  7  3  6  4  4";
+        assert_eq!(149, score_str(a));
+    }
+
+    #[test]
+    fn test_strikes() {
+        assert_eq!(25, score(&[10, 5, 0, 5, 0]));
+        assert_eq!(31, score(&[10, 5, 2, 5, 2]));
+        assert_eq!(23, score(&[5, 0, 10, 2, 2]));
+        assert_eq!(40, score(&[10, 10, 2, 2]));
+        assert_eq!(70, score(&[10, 10, 10, 2, 2]));
+        assert_eq!(100, score(&[10, 10, 10, 10, 2, 2]));
+    }
+
+    #[test]
+    fn test_spares_and_strikes() {
+        // strike before spare
+        assert_eq!(32, score(&[10, 5, 5, 2]));
+        assert_eq!(36, score(&[10, 5, 5, 2, 2]));
+        assert_eq!(58, score(&[10, 5, 5, 10, 2, 2]));
+        assert_eq!(60, score(&[10, 5, 5, 10, 5, 5]));
+        assert_eq!(72, score(&[10, 5, 5, 10, 5, 5, 2]));
+        assert_eq!(61, score(&[10, 10, 5, 10, 5, 2, 2]));
+
+        // spare before strike
+        assert_eq!(40, score(&[5, 5, 10, 5, 5]));
+        assert_eq!(52, score(&[5, 5, 10, 5, 5, 2]));
+    }
+
+    #[test]
+    fn test_last_frame() {
+        let f = |x: &[T]| pad(x).scores().sum::<T>();
+
+        // last frame score = sum(frame)
+        assert_eq!(20, f(&[10, 6, 4]));
+        assert_eq!(15, f(&[5, 5, 5]));
+        assert_eq!(15, f(&[1, 9, 5]));
+        assert_eq!(15, f(&[10, 0, 5]));
+        assert_eq!(24, f(&[10, 10, 4]));
+        assert_eq!(20, f(&[5, 5, 10]));
+
+        // extra rolls after spare/strike
+        assert_eq!(20, f(&[10, 6, 4, 1]));
+        assert_eq!(24, f(&[10, 10, 4, 1]));
+        assert_eq!(15, f(&[5, 5, 5, 1]));
+        assert_eq!(15, f(&[1, 9, 5, 1]));
+        assert_eq!(15, f(&[10, 0, 5, 1]));
+        assert_eq!(20, f(&[5, 5, 10, 1]));
+
+        // extra rolls after open frame
+        assert_eq!(9, f(&[4, 5, 5, 1]));
+        assert_eq!(5, f(&[5, 0, 5, 1]));
+        assert_eq!(0, f(&[0, 0, 5, 1]));
+
+        // invalid inputs
+        assert_eq!(1, f(&[1, 10, 0, 1, 1]));
+        assert_eq!(2, f(&[1, 10, 1, 1, 1]));
+        assert_eq!(11, f(&[5, 10, 5, 1, 1, 1]));
+    }
+
+    #[test]
+    fn test_rules() {
+        let score = |rules: Rules, rolls: &[T]| {
+            let mut g = Game::new(rules);
+            for r in rolls {
+                let _ = g.roll(r);
+            }
+            (g.total(), g.is_over())
+        };
+        assert_eq!(LAST_FRAME, Rules::default().last_frame());
+
+        // nine pins on the first ball count as a strike
+        assert_eq!((300, true), score(Rules::nine_pin_no_tap(), &[9; 12]));
+        let no_tap = Rules::nine_pin_no_tap();
+        assert_eq!((56, false), score(no_tap, &[9, 9, 8, 1]));
+
+        // three balls a frame, and no bonus for clearing on the third
+        let candlepin = Rules::candlepin();
+        assert_eq!((90, true), score(candlepin.clone(), &[3; 30]));
+        assert_eq!((16 + 9, false), score(candlepin.clone(), &[10, 3, 3, 3]));
+        assert_eq!((13 + 9, false), score(candlepin.clone(), &[5, 5, 3, 3, 3]));
+        assert_eq!((10, false), score(candlepin.clone(), &[2, 3, 5]));
+        assert_eq!((300, true), score(candlepin, &[10; 12]));
+
+        let five_pin = Rules::five_pin();
+        assert_eq!((450, true), score(five_pin.clone(), &[15; 12]));
+        assert_eq!((25, false), score(five_pin.clone(), &[5, 10, 10]));
+        assert_eq!((15, false), score(five_pin, &[14, 1, 2, 3, 10]));
+    }
+
+    #[test]
+    fn test_projection() {
+        assert_eq!((300, 0), Game::default().projection());
+        assert_eq!((300, 300), mock(&[10; 12]).projection());
+        assert_eq!((280, 30), mock(&[10, 5, 5]).projection());
+        assert_eq!((248, 19), mock(&[9, 0, 9, 0, 1]).projection());
+        assert_eq!((300, 10), mock(&[10]).projection());
+        assert_eq!((30, 20), pad(&[10, 10]).projection());
+        assert_eq!((20, 10), pad(&[5, 5]).projection());
+
+        let g = mock(&[10, 5, 5]);
+        assert_eq!(Some(vec![]), g.needed(30));
+        assert_eq!(Some(vec![10]), g.needed(31));
+        assert_eq!(Some(vec![10]), g.needed(50));
+        assert_eq!(Some(vec![10, 10]), g.needed(51));
+        assert_eq!(Some(vec![10; 10]), g.needed(280));
+        assert_eq!(None, g.needed(281));
+        assert_eq!(Some(vec![1]), mock(&[9]).needed(10));
+        assert_eq!(None, mock(&[10; 12]).needed(301));
+
+        // every clearing ball under other rules is a whole rack
+        let g = Game::new(Rules::five_pin());
+        assert_eq!((450, 0), g.projection());
+        assert_eq!(Some(vec![15]), g.needed(15));
+    }
+
+    #[test]
+    fn test_leave() {
+        let leave = |s: &str| s.parse::<Leave>().unwrap();
+        let leaves =
+            |g: &Game| g.frames().iter().map(Frame::leave).collect::<Vec<_>>();
+        let mut g = Game::default();
+        assert_eq!(
+            Err(RollError::LeaveMismatch {
+                knocked: 8,
+                attempted: 9
+            }),
+            g.roll_leave(&9, &leave("7-10"))
+        );
+        assert_eq!(
+            Err(RollError::InvalidLeave),
+            g.roll_leave(&9, &leave("11"))
+        );
+        g.roll_leave(&8, &leave("7-10")).unwrap();
+        g.roll(&1).unwrap();
+        g.roll_leave(&6, &leave("1-2-4-10")).unwrap();
+        assert_eq!(
+            vec![Some(leave("7-10")), Some(leave("1-2-4-10"))],
+            leaves(&g)
+        );
+
+        // later leaves survive an edit while they still start a frame
+        g.edit(0, 1, &2).unwrap();
+        assert_eq!(
+            vec![Some(leave("7-10")), Some(leave("1-2-4-10"))],
+            leaves(&g)
+        );
+        g.edit(0, 0, &10).unwrap();
+        assert_eq!(vec![None, None, None], leaves(&g));
+
+        // a no-tap strike still remembers the pin left standing
+        let mut g = Game::new(Rules::nine_pin_no_tap());
+        g.roll_leave(&9, &leave("10")).unwrap();
+        assert_eq!(vec![10], g.frames()[0].rolls());
+        assert_eq!(Some(leave("10")), g.frames()[0].leave());
+    }
+
+    #[test]
+    fn test_current_frame() {
+        let rules = Rules {
+            scoring: Scoring::CurrentFrame,
+            ..Rules::default()
+        };
+        let mock = |rolls: &[T]| {
+            let mut g = Game::new(rules.clone());
+            for r in rolls {
+                let _ = g.roll(r);
+            }
+            g
+        };
+        // no bonus balls in the last frame, and nothing waits on lookahead
+        let g = mock(&[10; 11]);
+        assert_eq!((300, true), (g.total(), g.is_over()));
+        assert_eq!(vec![30], mock(&[10]).scores().collect::<Vec<_>>());
+        assert_eq!(vec![17], mock(&[7, 3]).scores().collect::<Vec<_>>());
+        assert_eq!(
+            vec![9, 5],
+            mock(&[9, 0, 2, 3]).scores().collect::<Vec<_>>()
+        );
+        assert_eq!(0, mock(&[7]).pending().count());
+
+        let g =
+            mock(&[9, 1, 9, 1, 9, 1, 9, 1, 9, 1, 9, 1, 9, 1, 9, 1, 9, 1, 9, 1]);
+        assert_eq!((190, true), (g.total(), g.is_over()));
+        let g = mock(&[0; 20]);
+        assert_eq!((0, true), (g.total(), g.is_over()));
+        assert_eq!(Err(RollError::GameOver), mock(&[10; 10]).roll(&10));
+    }
+
+    #[test]
+    fn test_roll_errors() {
+        let mut g = mock(&[3]);
+        assert_eq!(
+            Err(RollError::TooManyPins {
+                remaining: 7,
+                attempted: 8
+            }),
+            g.roll(&8)
+        );
+        assert_eq!(Ok(()), g.roll(&7));
+
+        let mut g = mock(&[10; 12]);
+        assert!(g.is_over());
+        assert_eq!(Err(RollError::GameOver), g.roll(&0));
+    }
+
+    #[test]
+    fn test_undo() {
+        fn f(p: T, rolls: impl Iterator<Item = T>) {
+            let mut a = Game::default();
+            let mut b = Game::default();
+            for r in rolls {
+                let _ = a.roll(&r);
+                let _ = b.roll(&r);
+            }
+            if b.roll(&p).is_ok() {
+                b.undo();
+            };
+            assert_eq!(a.scores().sum::<T>(), b.scores().sum());
+        }
+
+        for p in 0..=11 {
+            for x in 0..=10 {
+                for y in 0..=30 {
+                    f(p, std::iter::repeat_n(x, y));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_incremental_totals() {
+        // Full rescan, as scoring worked before totals were kept.
+        fn naive(g: &Game) -> Vec<Option<T>> {
+            let rolls = g.frames().iter().flat_map(Frame::rolls);
+            let mut n = 0;
+            let mut total = 0;
+            (0..=LAST_FRAME)
+                .map(|i| {
+                    let f = g.frames().get(i)?;
+                    n += f.len();
+                    let mut lookahead = rolls.clone().skip(n);
+                    let bonus = match g.completion_status(i) {
+                        Incomplete => return None,
+                        Strike => lookahead.next()? + lookahead.next()?,
+                        Spare => *lookahead.next()?,
+                        NoBonus => 0,
+                    };
+                    total += f.sum() + bonus;
+                    Some(total)
+                })
+                .collect()
+        }
+
+        let mut rng = Rng::new(123);
+        for _ in 0..1000 {
+            let mut g = Game::default();
+            for _ in 0..30 {
+                let p = rng.range(0..12) as T;
+                let _ = g.roll(&p);
+                if p == 11 {
+                    g.undo();
+                }
+                let totals = (0..=LAST_FRAME).map(|i| g.running_total(i));
+                assert_eq!(naive(&g), totals.collect::<Vec<_>>());
+                assert_eq!(g.total(), g.scores().sum());
+            }
+        }
+
+        // past the last scored frame
+        let g = mock(&[3]);
+        assert_eq!(None, g.score(0));
+        assert_eq!(None, g.score(5));
+        assert_eq!(Some(7), mock(&[3, 4]).score(0));
+    }
+
+    #[test]
+    fn test_redo() {
+        let mut g = mock(&[3, 4, 10, 5]);
+        let before = g.clone();
+        assert_eq!(None, g.redo());
+        for _ in 0..2 {
+            g.undo();
+        }
+        assert_eq!(7, g.total());
+        for _ in 0..2 {
+            assert_eq!(Some(()), g.redo());
+        }
+        assert_eq!(None, g.redo());
+        assert_eq!(before, g);
+
+        // a new roll discards what was undone
+        g.undo();
+        g.roll(&2).unwrap();
+        assert_eq!(None, g.redo());
+        assert_eq!(mock(&[3, 4, 10, 2]).frames(), g.frames());
+    }
+
+    #[test]
+    fn test_edit() {
+        let mut g = mock(&[3, 4, 10, 5, 2]);
+        assert_eq!(Ok((vec![], 0)), g.edit(0, 1, &7));
+        assert_eq!(mock(&[3, 7, 10, 5, 2]).frames(), g.frames());
+        assert_eq!(20 + 17 + 7, g.total());
+
+        // nothing fits after 8 until the 2 that makes the spare
+        let mut g = mock(&[3, 4, 10, 5, 2]);
+        let (invalidated, _) = g.edit(0, 0, &8).unwrap();
+        let error = RollError::TooManyPins {
+            remaining: 2,
+            attempted: 4,
+        };
+        assert_eq!(error, invalidated[0].error);
+        assert_eq!(
+            vec![(0, 1, 4), (1, 0, 10), (2, 0, 5)],
+            invalidated
+                .iter()
+                .map(|x| (x.frame, x.roll, x.pins))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(mock(&[8, 2]).frames(), g.frames());
+
+        let mut g = mock(&[3, 4]);
+        let before = g.clone();
+        assert_eq!(
+            Err(RollError::TooManyPins {
+                remaining: 7,
+                attempted: 8
+            }),
+            g.edit(0, 1, &8)
+        );
+        assert_eq!(
+            Err(RollError::NoSuchRoll { frame: 0, roll: 2 }),
+            g.edit(0, 2, &0)
+        );
+        assert_eq!(
+            Err(RollError::NoSuchRoll { frame: 3, roll: 0 }),
+            g.edit(3, 0, &0)
+        );
+        assert_eq!(before, g);
+    }
+
+    #[test]
+    fn test_fouls() {
+        let mut g = Game::default();
+        g.deliver(&Delivery::Foul).unwrap();
+        g.roll(&10).unwrap();
+        g.deliver(&Delivery::DeadBall).unwrap();
+        g.roll(&9).unwrap();
+        g.deliver(&Delivery::Foul).unwrap();
+        // fouls score as gutters but stay on the sheet
+        assert_eq!("F/ 9F", g.to_string());
+        assert_eq!(19 + 9, g.total());
+        assert!(g.frames()[1].is_foul(1));
+        assert_eq!(&[0], g.frames()[1].dead_balls());
+
+        // undone and redone in the order they were bowled
+        let before = g.clone();
+        for _ in 0..3 {
+            g.undo().unwrap();
+        }
+        assert_eq!("F/", g.to_string());
+        assert!(g.frames()[1].dead_balls().is_empty());
+        for _ in 0..3 {
+            g.redo().unwrap();
+        }
+        assert_eq!(before, g);
+
+        // a pinfall correction keeps the fouls and dead balls around it
+        assert_eq!(Ok((vec![], 0)), g.edit(1, 0, &7));
+        assert_eq!("F/ 7F", g.to_string());
+        assert_eq!(&[0], g.frames()[1].dead_balls());
+        assert_eq!(Ok((vec![], 0)), g.edit(1, 1, &2));
+        assert_eq!("F/ 72", g.to_string());
+
+        // dead balls go with a frame that is no longer reached
+        let mut g = mock(&[3, 4]);
+        g.deliver(&Delivery::DeadBall).unwrap();
+        g.roll(&5).unwrap();
+        let (invalidated, dead_balls) = g.edit(0, 0, &9).unwrap();
+        assert_eq!((2, 1), (invalidated.len(), dead_balls));
+        assert_eq!("9", g.to_string());
+        assert!(g.frames()[0].dead_balls().is_empty());
+
+        let mut g = mock(&[10; 12]);
+        assert_eq!(Err(RollError::GameOver), g.deliver(&Delivery::DeadBall));
+        assert_eq!(Err(RollError::GameOver), g.deliver(&Delivery::Foul));
+    }
+
+    #[test]
+    fn test_normal_frame_to_string() {
+        let f = |x: &[T]| mock(x).frame_to_string(0);
+
+        // strike
+        assert_eq!("X".to_string(), f(&[10, 0]));
+
+        // spare
+        assert_eq!("-/".to_string(), f(&[0, 10]));
+        assert_eq!("4/".to_string(), f(&[4, 6]));
+        assert_eq!("5/".to_string(), f(&[5, 5]));
+        assert_eq!("6/".to_string(), f(&[6, 4]));
+
+        // open frame
+        assert_eq!("".to_string(), f(&[]));
+        assert_eq!("-".to_string(), f(&[0]));
+        assert_eq!("1".to_string(), f(&[1]));
+        assert_eq!("12".to_string(), f(&[1, 2]));
+        assert_eq!("--".to_string(), f(&[0, 0]));
+        assert_eq!("-4".to_string(), f(&[0, 4]));
+        assert_eq!("4-".to_string(), f(&[4, 0]));
+    }
+
+    #[test]
+    fn test_last_frame_to_string() {
+        let f = |x: &[T]| pad(x).frame_to_string(LAST_FRAME);
+
+        // 1 roll
+        assert_eq!("1".to_string(), f(&[1]));
+        assert_eq!("2".to_string(), f(&[2]));
+        assert_eq!("3".to_string(), f(&[3]));
+        assert_eq!("4".to_string(), f(&[4]));
+        assert_eq!("5".to_string(), f(&[5]));
+        assert_eq!("6".to_string(), f(&[6]));
+        assert_eq!("7".to_string(), f(&[7]));
+        assert_eq!("8".to_string(), f(&[8]));
+        assert_eq!("9".to_string(), f(&[9]));
+        assert_eq!("X".to_string(), f(&[10]));
+
+        // 2 rolls
+        assert_eq!("--".to_string(), f(&[0, 0]));
+        assert_eq!("9-".to_string(), f(&[9, 0]));
+        assert_eq!("9/".to_string(), f(&[9, 1]));
+        assert_eq!("1/".to_string(), f(&[1, 9]));
+        assert_eq!("X-".to_string(), f(&[10, 0]));
+        assert_eq!("X5".to_string(), f(&[10, 5]));
+        assert_eq!("XX".to_string(), f(&[10, 10]));
+
+        // 3 rolls with strikes
+        assert_eq!("X--".to_string(), f(&[10, 0, 0]));
+        assert_eq!("-/-".to_string(), f(&[0, 10, 0]));
+        assert_eq!("--".to_string(), f(&[0, 0, 0]));
+        assert_eq!("--".to_string(), f(&[0, 0, 10]));
+        assert_eq!("XX-".to_string(), f(&[10, 10, 0]));
+        assert_eq!("-/X".to_string(), f(&[0, 10, 10]));
+        assert_eq!("XXX".to_string(), f(&[10, 10, 10]));
+
+        // 3 rolls with spares
+        assert_eq!("5/5".to_string(), f(&[5, 5, 5]));
+        assert_eq!("5/-".to_string(), f(&[5, 5, 0]));
+        assert_eq!("5-".to_string(), f(&[5, 0, 0]));
+        assert_eq!("44".to_string(), f(&[4, 4, 4]));
+        assert_eq!("4/4".to_string(), f(&[4, 6, 4]));
+        assert_eq!("6/X".to_string(), f(&[6, 4, 10]));
+        assert_eq!("4/X".to_string(), f(&[4, 6, 10]));
+    }
+}
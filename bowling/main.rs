@@ -1,453 +1,1045 @@
-use std::io::Write;
-use CompletionStatus::*;
+use bowling::lane::Lane;
+use bowling::league::{self, League};
+use bowling::odds::{self, Model};
+use bowling::stats::Stats;
+use bowling::tournament::{self, Tournament};
+use bowling::{batch, export, json, save, Delivery, Game, Leave, Rules};
+use std::io::{BufRead, IsTerminal, Write};
+use std::net::TcpListener;
+use std::time::Instant;
+use std::{env, fs, thread};
 
-// Rules
-// Game has 10 frames.
-// Each frame starts with 10 pins.
-// Max 2 rolls per frame.
-// Last frame only: spare OR strike -> exactly 3 rolls.
-const LAST_FRAME: usize = 9;
-const MAX_PINS: T = 10;
-const FRAME_SIZE: usize = 2;
-const FRAME_SIZE_LAST: usize = FRAME_SIZE + 1;
+mod serve;
+mod tui;
 
-#[derive(PartialEq)]
-enum CompletionStatus {
-    NoBonus,
-    Strike,
-    Spare,
-    Incomplete,
+const AUTOSAVE: &str = "./autosave.txt";
+const ADDRESS: &str = "127.0.0.1:7878";
+
+fn main() {
+    let args = env::args().collect::<Vec<_>>();
+    match &args[1..] {
+        [] => repl(),
+        [flag] if flag == "--script" => {
+            script(std::io::stdin().lock(), std::io::stdout().lock())
+        }
+        [flag, path] if flag == "--script" => match fs::File::open(path) {
+            Ok(file) => {
+                script(std::io::BufReader::new(file), std::io::stdout().lock())
+            }
+            Err(e) => eprintln!("{path}: {e}"),
+        },
+        [cmd, input, opts @ ..] if cmd == "score" => match parse_opts(opts) {
+            Some(opts) => score(input, opts),
+            None => usage(),
+        },
+        [cmd, input] if cmd == "verify" => verify(input),
+        [cmd, dir] if cmd == "stats" => stats(dir),
+        [cmd, opts @ ..] if cmd == "enumerate" => match opts {
+            [] => enumerate(Rules::default()),
+            [flag, rules] if flag == "-r" => match rules.parse() {
+                Ok(rules) => enumerate(rules),
+                Err(e) => eprintln!("{e}"),
+            },
+            _ => usage(),
+        },
+        [cmd, file, args @ ..] if cmd == "league" => league(file, args),
+        [cmd, file, args @ ..] if cmd == "tournament" => tournament(file, args),
+        [cmd, lanes, address @ ..] if cmd == "serve" && address.len() < 2 => {
+            let address = address.first().map_or(ADDRESS, |a| a.as_str());
+            match lanes.parse() {
+                Ok(lanes @ 1..) => match TcpListener::bind(address) {
+                    Ok(listener) => {
+                        println!("Serving {lanes} lanes on {address}");
+                        serve::serve(listener, lanes)
+                    }
+                    Err(e) => eprintln!("{address}: {e}"),
+                },
+                _ => usage(),
+            }
+        }
+        [cmd, input, output] if cmd == "export" => export_file(input, output),
+        [cmd, games, opts @ ..] if cmd == "simulate" => {
+            match (games.parse(), parse_sim_opts(opts)) {
+                (Ok(games), Some(opts)) => simulate(games, opts),
+                _ => usage(),
+            }
+        }
+        _ => usage(),
+    }
+}
+
+fn usage() {
+    println!("Usage: bowling");
+    println!("       bowling --script [commands file, or stdin]");
+    println!("       bowling score <input> [options]");
+    println!("       bowling verify <input>");
+    println!("       bowling stats <dir of saved games>");
+    println!("       bowling simulate <games> [options]");
+    println!("       bowling enumerate [-r <rules>]");
+    println!("       bowling export <saved game> <output .html or .svg>");
+    println!("       bowling league <file> [team <name> <bowlers>...]");
+    println!("       bowling league <file> [week <saved game>...]");
+    println!("       bowling league <file> [handicap <basis> <percent>]");
+    println!("       bowling tournament <file> [new <format> <entrants>...]");
+    println!("       bowling tournament <file> [game <a> <score> <b> <score>]");
+    println!("       bowling tournament <file> [result <saved game>]");
+    println!("Tournament formats: match-play, stepladder. An entrant bowling");
+    println!("Baker games is given as e.g. \"Pin Pals: Ann, Bo, Cy\".");
+    println!("       bowling serve <lanes> [address, defaults to {ADDRESS}]");
+    println!("Score options:");
+    println!("  -o <output>   write to a file instead of stdout");
+    println!("  -j <threads>  defaults to the number of cpus");
+    println!("  -r <rules>    e.g. five-pin, or no_tap=9 balls=3");
+    println!("                scoring=current-frame for World Bowling scoring");
+    println!("  -n            input is scoresheet notation, e.g. X 7/ 9- 81");
+    println!("  -m            output scoresheet notation instead of scores");
+    println!("                -n and -m need a rack of at most 10 pins");
+    println!("Simulate options:");
+    println!("  -s <seed>     defaults to 0, same seed same games");
+    println!("  -r <rules>    as for score");
+    println!("  -x <rate>     chance of a strike, defaults to 0.25");
+    println!("  -p <rate>     chance of a spare, defaults to 0.5");
+    println!("  -l <dir>      learn pinfall from saved games instead");
 }
 
-type T = u32;
+// Scores `input` again with the reference scorer, for the default rules.
+fn verify(input: &str) {
+    let input = match fs::read_to_string(input) {
+        Ok(s) => s,
+        Err(e) => return eprintln!("{input}: {e}"),
+    };
+    let mismatches = bowling::reference::verify(&input);
+    for m in &mismatches {
+        println!("{m}");
+    }
+    let lines = input.lines().count();
+    println!("Verified {lines} lines, {} disagreements", mismatches.len());
+    if !mismatches.is_empty() {
+        std::process::exit(1);
+    }
+}
 
-struct Game {
-    frames: Vec<Vec<T>>,
+// Every distinct game under `rules`, counted by final score.
+fn enumerate(rules: Rules) {
+    let start = Instant::now();
+    let counts = match bowling::enumerate::enumerate(&Game::new(rules)) {
+        Ok(counts) => counts,
+        Err(e) => return eprintln!("{e}"),
+    };
+    let secs = start.elapsed().as_secs_f64();
+    let games = bowling::enumerate::games(&counts);
+    println!("Enumerated {games} distinct games in {secs:.3}s");
+    println!("Mean {:.2}", bowling::enumerate::mean(&counts));
+    let top = counts.len() - 1;
+    println!("Ways to score {top}: {}", counts[top]);
+    let mode = (0..counts.len()).max_by_key(|&s| counts[s]).unwrap();
+    println!("Most common score: {mode} ({} games)", counts[mode]);
+    println!("{:>5} {:>22}", "Score", "Games");
+    for (score, n) in counts.iter().enumerate().filter(|(_, &n)| n > 0) {
+        println!("{score:>5} {n:>22}");
+    }
 }
 
-impl Game {
-    fn new() -> Game {
-        Game {
-            frames: vec![vec![]],
-        }
+// An SVG scoresheet for a `.svg` path, a printable page for anything else.
+fn export(lane: &Lane, path: &str) -> String {
+    let path = std::path::Path::new(path);
+    if path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("svg"))
+    {
+        return export::svg(lane);
     }
+    let title = path.file_stem().unwrap_or_default().to_string_lossy();
+    export::html(lane, &title)
+}
 
-    fn roll(&mut self, p: &T) -> Result<(), &str> {
-        self.valid(p)?;
-        let i = self.frames.len() - 1;
-        self.frames[i].push(*p);
-        if i < LAST_FRAME && self.completion_status(i) != Incomplete {
-            self.frames.push(vec![]);
-        }
-        Ok(())
+fn export_file(input: &str, output: &str) {
+    let lane = match fs::read_to_string(input).map(|s| save::load(&s)) {
+        Ok(Ok(lane)) => lane,
+        Ok(Err(e)) => return eprintln!("{input}: {e}"),
+        Err(e) => return eprintln!("{input}: {e}"),
+    };
+    if let Err(e) = fs::write(output, export(&lane, output)) {
+        eprintln!("{output}: {e}");
     }
+}
 
-    fn score(&self, i: usize) -> Option<T> {
-        self.frames.get(i)?;
-        let sum = self.sum(i);
-        match self.completion_status(i) {
-            Incomplete => None,
-            Strike => {
-                let n = self.frames.iter().take(i + 1).flatten().count();
-                let mut lookahead = self.frames.iter().flatten().skip(n);
-                Some(MAX_PINS + lookahead.next()? + lookahead.next()?)
+// Files that can't be read or loaded are reported and skipped.
+fn load_dir(dir: &str) -> Vec<Lane> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{dir}: {e}");
+            return vec![];
+        }
+    };
+    let mut paths = entries
+        .filter_map(|e| match e {
+            Ok(e) => Some(e.path()),
+            Err(e) => {
+                eprintln!("{dir}: {e}");
+                None
             }
-            Spare => Some(MAX_PINS + self.frames.get(i + 1)?.get(0)?),
-            NoBonus => Some(sum),
+        })
+        .filter(|p| p.is_file())
+        .collect::<Vec<_>>();
+    paths.sort();
+    let mut lanes = vec![];
+    for path in paths {
+        match fs::read_to_string(&path).map(|s| save::load(&s)) {
+            Ok(Ok(lane)) => lanes.push(lane),
+            Ok(Err(e)) => eprintln!("{}: {e}", path.display()),
+            Err(e) => eprintln!("{}: {e}", path.display()),
         }
     }
+    lanes
+}
 
-    fn scores(&self) -> impl Iterator<Item = T> + '_ {
-        (0..self.frames.len()).flat_map(|i| self.score(i))
+fn stats(dir: &str) {
+    let mut bowlers: Vec<(String, Stats)> = vec![];
+    for lane in load_dir(dir) {
+        for b in lane.bowlers() {
+            let i = match bowlers.iter().position(|(n, _)| n == b.name()) {
+                Some(i) => i,
+                None => {
+                    bowlers.push((b.name().to_string(), Stats::default()));
+                    bowlers.len() - 1
+                }
+            };
+            bowlers[i].1.add(b.game());
+        }
     }
-
-    fn valid<'a>(&self, p: &T) -> Result<(), &'a str> {
-        let i = self.frames.len() - 1;
-        let remaining_pins = if self.sum(i) % MAX_PINS == 0 {
-            MAX_PINS
-        } else {
-            MAX_PINS - self.frames[i].last().unwrap()
-        };
-        if i == LAST_FRAME && self.completion_status(i) != Incomplete {
-            Err("Game Over!")
-        } else if p > &remaining_pins {
-            Err("Invalid # of pins!")
-        } else {
-            Ok(())
+    println!(
+        "{:12} {:>5} {:>6} {:>4} {:>7} {:>7} {:>6} {:>8} {:>8} {:>10} {:>7} {:>5}",
+        "Bowler",
+        "Games",
+        "Avg",
+        "High",
+        "Strike%",
+        "Spare%",
+        "Open%",
+        "1st ball",
+        "10th avg",
+        "10th mark%",
+        "Split%",
+        "Fouls"
+    );
+    for (name, s) in bowlers.iter().filter(|(_, s)| s.games > 0) {
+        println!(
+            "{:12} {:>5} {:>6.1} {:>4} {:>7.1} {:>7.1} {:>6.1} {:>8.2} {:>8.1} {:>10.1} {:>7.1} {:>5}",
+            name,
+            s.games,
+            s.average(),
+            s.high,
+            s.strike_rate() * 100.0,
+            s.spare_rate() * 100.0,
+            s.open_rate() * 100.0,
+            s.first_ball_average(),
+            s.tenth_average(),
+            s.tenth_mark_rate() * 100.0,
+            s.splits.rate() * 100.0,
+            s.fouls
+        );
+    }
+    for (name, s) in bowlers.iter().filter(|(_, s)| !s.leaves.is_empty()) {
+        println!("\n{name}: leaves converted");
+        let mut leaves = s.leaves.iter().collect::<Vec<_>>();
+        leaves.sort_by_key(|(_, c)| std::cmp::Reverse(c.attempts));
+        for (leave, c) in leaves {
+            println!(
+                "  {:12} {:>4}/{:<4} {:>6.1}%  {}",
+                leave.to_string(),
+                c.converted,
+                c.attempts,
+                c.rate() * 100.0,
+                describe(leave)
+            );
         }
     }
+}
 
-    fn completion_status(&self, i: usize) -> CompletionStatus {
-        let len = self.frames[i].len();
-        let sum = self.sum(i);
-        if len == FRAME_SIZE_LAST || (len == FRAME_SIZE && sum < MAX_PINS) {
-            NoBonus
-        } else if i < LAST_FRAME && len == 1 && sum == MAX_PINS {
-            Strike
-        } else if i < LAST_FRAME && len == FRAME_SIZE && sum == MAX_PINS {
-            Spare
-        } else {
-            Incomplete
+// e.g. `split (bedposts)`, empty for an unremarkable leave.
+fn describe(leave: &Leave) -> String {
+    let kind = if leave.is_split() {
+        "split"
+    } else if leave.is_washout() {
+        "washout"
+    } else {
+        ""
+    };
+    match (kind, leave.name()) {
+        ("", None) => "".to_string(),
+        ("", Some(name)) => name.to_string(),
+        (kind, None) => kind.to_string(),
+        (kind, Some(name)) => format!("{kind} ({name})"),
+    }
+}
+
+// Standings, after adding a team or a week of saved games if asked to. The
+// league file is created with the default handicap by the first team.
+fn league(path: &str, args: &[String]) {
+    let mut league = match fs::read_to_string(path) {
+        Ok(s) => match league::load(&s) {
+            Ok(league) => league,
+            Err(e) => return eprintln!("{path}: {e}"),
+        },
+        Err(_)
+            if args.first().is_some_and(|a| a == "team" || a == "handicap") =>
+        {
+            League::default()
+        }
+        Err(e) => return eprintln!("{path}: {e}"),
+    };
+    let changed = match args {
+        [] => Ok(false),
+        [cmd, name, bowlers @ ..] if cmd == "team" => {
+            let bowlers = bowlers.iter().map(|b| b.as_str());
+            league
+                .add_team(name, &bowlers.collect::<Vec<_>>())
+                .map(|()| true)
+        }
+        [cmd, basis, percent] if cmd == "handicap" => {
+            match (basis.parse(), percent.parse()) {
+                (Ok(basis), Ok(percent)) => {
+                    league.set_handicap(basis, percent).map(|()| true)
+                }
+                _ => return usage(),
+            }
+        }
+        [cmd, games @ ..] if cmd == "week" && !games.is_empty() => {
+            let mut lanes = vec![];
+            for game in games {
+                let loaded = fs::read_to_string(game)
+                    .map_err(|e| e.to_string())
+                    .and_then(|s| save::load(&s).map_err(|e| e.to_string()));
+                match loaded {
+                    Ok(lane) => lanes.push(lane),
+                    Err(e) => return eprintln!("{game}: {e}"),
+                }
+            }
+            league
+                .week_from_lanes(&lanes)
+                .and_then(|week| league.add_week(week))
+                .map(|()| true)
+        }
+        _ => return usage(),
+    };
+    match changed {
+        Ok(true) => {
+            if let Err(e) = fs::write(path, league::save(&league)) {
+                eprintln!("{path}: {e}");
+                std::process::exit(1);
+            }
         }
+        Ok(false) => {}
+        Err(e) => return eprintln!("{e}"),
     }
+    standings(&league);
+}
 
-    fn sum(&self, i: usize) -> T {
-        self.frames[i].iter().sum()
+fn standings(league: &League) {
+    let teams = league.teams();
+    println!("After {} weeks", league.weeks().len());
+    println!(
+        "{:20} {:>6} {:>8} {:>8}",
+        "Team", "Points", "Scratch", "With hcp"
+    );
+    for s in league.standings() {
+        println!(
+            "{:20} {:>6.1} {:>8} {:>8}",
+            teams[s.team].name, s.points, s.scratch, s.handicap
+        );
+    }
+    println!(
+        "\n{:12} {:20} {:>5} {:>5} {:>4} {:>6} {:>4}",
+        "Bowler", "Team", "Games", "Avg", "High", "Series", "Hcp"
+    );
+    for b in league.bowlers() {
+        let average = b.average.map_or("-".to_string(), |a| a.to_string());
+        println!(
+            "{:12} {:20} {:>5} {:>5} {:>4} {:>6} {:>4}",
+            b.name,
+            teams[b.team].name,
+            b.games,
+            average,
+            b.high_game,
+            b.high_series,
+            b.handicap
+        );
     }
+}
 
-    fn undo(&mut self) -> Option<()> {
-        if self.frames[0].is_empty() {
-            None
-        } else {
-            if self.frames.last()?.is_empty() {
-                self.frames.pop();
+// The bracket, after starting it or recording a game if asked to.
+fn tournament(path: &str, args: &[String]) {
+    let new = args.first().is_some_and(|a| a == "new");
+    if new && std::path::Path::new(path).exists() {
+        return eprintln!("{path}: Already exists!");
+    }
+    let mut lane = None;
+    let result = match args {
+        [cmd, format, entrants @ ..] if cmd == "new" => format
+            .parse()
+            .and_then(|format| {
+                let entrants = entrants.iter().map(|e| {
+                    let (name, members) = e.split_once(':').unwrap_or((e, ""));
+                    let members = members.split(',').map(str::trim);
+                    let members = members.filter(|m| !m.is_empty());
+                    tournament::Entrant {
+                        name: name.trim().to_string(),
+                        members: members.map(|m| m.to_string()).collect(),
+                    }
+                });
+                Tournament::new(format, entrants.collect())
+            })
+            .map_err(|e| e.to_string()),
+        _ => fs::read_to_string(path)
+            .map_err(|e| format!("{path}: {e}"))
+            .and_then(|s| {
+                tournament::load(&s).map_err(|e| format!("{path}: {e}"))
+            }),
+    };
+    let mut t = match result {
+        Ok(t) => t,
+        Err(e) => return eprintln!("{e}"),
+    };
+    let recorded = match args {
+        [] => Ok(()),
+        [cmd, ..] if cmd == "new" => Ok(()),
+        [cmd, a, a_score, b, b_score] if cmd == "game" => {
+            match (a_score.parse(), b_score.parse()) {
+                (Ok(a_score), Ok(b_score)) => {
+                    t.record(a, a_score, b, b_score).map_err(|e| e.to_string())
+                }
+                _ => return usage(),
             }
-            self.frames.last_mut()?.pop();
-            Some(())
+        }
+        [cmd, game] if cmd == "result" => fs::read_to_string(game)
+            .map_err(|e| e.to_string())
+            .and_then(|s| save::load(&s).map_err(|e| e.to_string()))
+            .and_then(|l| {
+                t.record_lane(&l).map_err(|e| e.to_string())?;
+                lane = Some(l);
+                Ok(())
+            }),
+        _ => return usage(),
+    };
+    if let Err(e) = recorded {
+        return eprintln!("{e}");
+    }
+    if !args.is_empty() {
+        if let Err(e) = fs::write(path, tournament::save(&t)) {
+            eprintln!("{path}: {e}");
+            std::process::exit(1);
+        }
+    }
+    if let Some(lane) = lane {
+        baker(&t, &lane);
+    }
+    bracket(&t);
+}
+
+// Who bowled which frames, for entrants bowling Baker games.
+fn baker(t: &Tournament, lane: &Lane) {
+    for b in lane.bowlers() {
+        let Some(i) = t.position(b.name()) else {
+            continue;
+        };
+        let members = &t.entrants()[i].members;
+        let frames = tournament::baker_frames(members, b.game());
+        for (member, frames) in members.iter().zip(frames) {
+            let marks = frames.iter().map(|&f| b.game().frame_to_string(f));
+            let marks = marks.collect::<Vec<_>>().join(" ");
+            println!("{}: {member} {marks}", b.name());
         }
     }
+}
 
-    fn print(&self) {
-        for i in 0..=LAST_FRAME {
-            if i < LAST_FRAME {
-                print!("{}:[{:3}]  ", i + 1, self.frame_to_string(i));
-            } else {
-                println!("{}:[{:5}]", i + 1, self.frame_to_string(i));
-            }
+fn bracket(t: &Tournament) {
+    let name = |slot| match slot {
+        tournament::Slot::Entrant(i) => t.entrants()[i].name.as_str(),
+        tournament::Slot::Bye => "(bye)",
+        tournament::Slot::Waiting => "(to be decided)",
+    };
+    println!("{}, {} entrants", t.format(), t.entrants().len());
+    for (r, round) in t.rounds().iter().enumerate() {
+        println!("Round {}", r + 1);
+        for &(a, b) in round {
+            let games = t.games().iter().filter(|g| {
+                [a, b] == [g.a, g.b].map(tournament::Slot::Entrant)
+                    || [b, a] == [g.a, g.b].map(tournament::Slot::Entrant)
+            });
+            let games = games.map(|g| {
+                let (x, y) = (&t.entrants()[g.a].name, &t.entrants()[g.b].name);
+                format!("{x} {} - {} {y}", g.a_score, g.b_score)
+            });
+            let games = games.collect::<Vec<_>>().join(", ");
+            let line = format!("  {} vs {}  {games}", name(a), name(b));
+            println!("{}", line.trim_end());
         }
-        for i in 0..=LAST_FRAME {
-            let score = if self.score(i).is_some() {
-                self.scores().take(i + 1).sum::<T>().to_string()
-            } else {
-                "".to_string()
-            };
-            if i < LAST_FRAME {
-                print!("  [{:>3}]  ", score);
-            } else {
-                println!("   [{:>5}]", score);
+    }
+    match t.winner() {
+        Some(i) => println!("Winner: {}", t.entrants()[i].name),
+        None => {
+            for (a, b) in t.pending() {
+                let (a, b) = (&t.entrants()[a].name, &t.entrants()[b].name);
+                println!("Up next: {a} vs {b}");
             }
         }
     }
+}
 
-    fn frame_to_string(&self, i: usize) -> String {
-        if i >= self.frames.len() || self.frames[i].is_empty() {
-            return "".to_string();
+struct SimOpts {
+    seed: u64,
+    rules: Rules,
+    strike: f64,
+    spare: f64,
+    learn: Option<String>,
+}
+
+fn parse_sim_opts(opts: &[String]) -> Option<SimOpts> {
+    let mut parsed = SimOpts {
+        seed: 0,
+        rules: Rules::default(),
+        strike: 0.25,
+        spare: 0.5,
+        learn: None,
+    };
+    let mut opts = opts.iter();
+    while let Some(opt) = opts.next() {
+        match opt.as_str() {
+            "-s" => parsed.seed = opts.next()?.parse().ok()?,
+            "-r" => parsed.rules = opts.next()?.parse().ok()?,
+            "-x" => parsed.strike = opts.next()?.parse().ok()?,
+            "-p" => parsed.spare = opts.next()?.parse().ok()?,
+            "-l" => parsed.learn = Some(opts.next()?.clone()),
+            _ => return None,
         }
-        let f = &self.frames[i];
-        let parse = |i: usize| {
-            if f[i] == 10 {
-                "X".to_string()
-            } else {
-                f[i].to_string()
-            }
-        };
-        let sum = if f.len() < FRAME_SIZE_LAST {
-            self.sum(i)
-        } else {
-            f[0] + f[1]
-        };
-        if i == LAST_FRAME {
-            match (f.len(), f[0], sum) {
-                (3, MAX_PINS, _) => format!("X,{},{}", parse(1), parse(2)),
-                (2, MAX_PINS, _) => format!("X,{},", parse(1)),
-                (1, MAX_PINS, _) => "X,".to_string(),
-                (3, _, MAX_PINS) => format!("{},/,{}", parse(0), parse(2)),
-                (2, _, MAX_PINS) => format!("{},/,", parse(0)),
-                (3, _, _) => format!("{},{},{}", parse(0), parse(1), parse(2)),
-                (2, _, _) => format!("{},{}", parse(0), parse(1)),
-                (1, _, _) => format!("{},", parse(0)),
-                _ => unreachable!(),
+    }
+    Some(parsed)
+}
+
+fn simulate(games: usize, opts: SimOpts) {
+    let model = match &opts.learn {
+        Some(dir) => {
+            let lanes = load_dir(dir);
+            let games = lanes
+                .iter()
+                .flat_map(|l| l.bowlers().iter().map(|b| b.game()))
+                .filter(|g| g.rules() == &opts.rules)
+                .collect::<Vec<_>>();
+            Model::learn(&opts.rules, &games)
+        }
+        None => match Model::from_rates(&opts.rules, opts.strike, opts.spare) {
+            Some(model) => model,
+            None => return println!("Rates must be between 0 and 1!"),
+        },
+    };
+    let start = Instant::now();
+    let summary =
+        bowling::simulate::simulate(&opts.rules, &model, games, opts.seed);
+    let secs = start.elapsed().as_secs_f64();
+    println!("Simulated {games} games (seed {}) in {secs:.3}s", opts.seed);
+    if games == 0 {
+        return;
+    }
+    println!("Mean {:.1}", summary.mean());
+    let percentiles = [0.1, 0.25, 0.5, 0.75, 0.9].map(|q| {
+        format!("{:.0}%: {}", q * 100.0, summary.percentile(q).unwrap())
+    });
+    println!("Percentiles  {}", percentiles.join("  "));
+
+    let buckets = summary.histogram.chunks(10).map(|c| c.iter().sum());
+    let buckets = buckets.collect::<Vec<usize>>();
+    let first = buckets.iter().position(|&n| n > 0).unwrap();
+    let most = *buckets.iter().max().unwrap();
+    for (i, &n) in buckets.iter().enumerate().skip(first) {
+        let bar = "#".repeat((n * 50).div_ceil(most));
+        println!("{:>3}-{:<3} {bar} {n}", i * 10, i * 10 + 9);
+    }
+}
+
+struct ScoreOpts<'a> {
+    output: Option<&'a String>,
+    threads: usize,
+    batch: batch::Options,
+}
+
+fn parse_opts(opts: &[String]) -> Option<ScoreOpts<'_>> {
+    let mut parsed = ScoreOpts {
+        output: None,
+        threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        batch: batch::Options::default(),
+    };
+    let mut opts = opts.iter();
+    while let Some(opt) = opts.next() {
+        match opt.as_str() {
+            "-o" => parsed.output = Some(opts.next()?),
+            "-j" => {
+                parsed.threads = opts.next()?.parse().ok().filter(|&n| n > 0)?
             }
-        } else {
-            match (f.len(), sum) {
-                (1, MAX_PINS) => " X ".to_string(),
-                (1, _) => format!("{},", parse(0)),
-                (2, MAX_PINS) => format!("{},/", parse(0)),
-                (2, _) => format!("{},{}", parse(0), parse(1)),
-                _ => unreachable!(),
+            "-r" => parsed.batch.rules = opts.next()?.parse().ok()?,
+            "-n" => parsed.batch.notation = true,
+            "-m" => parsed.batch.marks = true,
+            _ => return None,
+        }
+    }
+    Some(parsed)
+}
+
+fn score(path: &str, opts: ScoreOpts) {
+    // Marks are a character a roll, so a rack of more than 10 pins has
+    // counts that can't be written.
+    let notation = opts.batch.notation || opts.batch.marks;
+    if notation && opts.batch.rules.rack() > 10 {
+        return eprintln!("-n and -m need a rack of at most 10 pins!");
+    }
+    let input = match fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(e) => return eprintln!("{path}: {e}"),
+    };
+    let threads = opts.threads;
+    let start = Instant::now();
+    let (ans, diagnostics) =
+        batch::score_parallel(&input, threads, &opts.batch);
+    let secs = start.elapsed().as_secs_f64();
+    for d in &diagnostics {
+        eprintln!("{d}");
+    }
+    match &opts.output {
+        Some(path) => {
+            if let Err(e) = fs::write(path, ans) {
+                return eprintln!("{path}: {e}");
             }
         }
+        None => print!("{ans}"),
     }
+    let lines = input.lines().count();
+    eprintln!(
+        "Scored {lines} lines on {threads} threads in {secs:.3}s ({:.0} lines/s)",
+        lines as f64 / secs
+    );
 }
 
-fn main() {
-    fn clear() {
-        std::process::Command::new("clear").status().unwrap();
+// Written beside the autosave and renamed over it, so a crash part way
+// through leaves the last one whole.
+fn autosave(lane: &Lane) -> std::io::Result<()> {
+    let tmp = format!("{AUTOSAVE}.tmp");
+    fs::write(&tmp, save::save(lane))?;
+    fs::rename(tmp, AUTOSAVE)
+}
+
+fn repl() {
+    let (lane, note) =
+        match fs::read_to_string(AUTOSAVE).map(|s| save::load(&s)) {
+            Ok(Ok(lane)) => (lane, "".to_string()),
+            Ok(Err(e)) => (Lane::new(), format!("Autosave not loaded: {e}")),
+            Err(_) => (Lane::new(), "".to_string()),
+        };
+    if tui::available() {
+        tui::run(lane, note);
+    } else {
+        line_mode(lane, note);
     }
+}
+
+// One command per line, reprinting the lane after every change. Used when
+// input is not a terminal.
+fn line_mode(mut lane: Lane, note: String) {
+    let tty = std::io::stdout().is_terminal();
+    let clear = || {
+        if tty {
+            print!("{}", tui::CLEAR);
+        }
+    };
 
     clear();
-    let mut game = Game::new();
+    if !note.is_empty() {
+        println!("{note}\n");
+    }
     let mut refresh = true;
-
     loop {
         if refresh {
-            game.print();
+            lane.print();
+            if let Err(e) = autosave(&lane) {
+                println!("Autosave failed: {e}");
+            }
         }
         print!(" > ");
         std::io::stdout().flush().unwrap();
 
-        let input = user_input();
-        match input.as_str() {
-            "exit" => {
+        let Some(input) = user_input() else {
+            break;
+        };
+        refresh = match command(&mut lane, &input) {
+            Reply::Exit => {
                 clear();
                 break;
             }
-            "restart" => {
-                game = Game::new();
+            Reply::Clear => {
                 clear();
-                refresh = true;
-            }
-            "undo" => {
-                game.undo();
-                println!();
-                refresh = true;
+                true
             }
-            "clear" => {
-                clear();
-                refresh = true;
+            Reply::Redraw(note) => {
+                println!("{note}");
+                true
             }
-            _ => {
-                let err = match input.parse().map(|x| game.roll(&x)) {
-                    Err(_) => "Invalid input!",
-                    Ok(Err(s)) => s,
-                    _ => "",
-                };
-                println!("{err}");
-                refresh = err.is_empty();
+            Reply::Message(msg) | Reply::Error(msg) => {
+                println!("{msg}");
+                false
             }
-        }
+        };
     }
 }
 
-fn user_input() -> String {
-    let mut buffer = String::new();
-    std::io::stdin().read_line(&mut buffer).unwrap();
-    buffer.trim().to_string()
+// REPL commands, one per line, each answered with a JSON line holding the
+// outcome and the whole lane. Starts from an empty lane and never touches
+// the autosave, so the same input always gives the same output.
+fn script(input: impl BufRead, mut out: impl Write) {
+    let mut lane = Lane::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => return eprintln!("line {}: {e}", i + 1),
+        };
+        let input = line.trim();
+        if input.is_empty() {
+            continue;
+        }
+        let (ok, message) = match command(&mut lane, input) {
+            Reply::Exit => break,
+            Reply::Clear => (true, "".to_string()),
+            Reply::Redraw(msg) | Reply::Message(msg) => (true, msg),
+            Reply::Error(e) => (false, e),
+        };
+        let reply = json::object(&[
+            ("command", json::string(input)),
+            ("ok", ok.to_string()),
+            ("message", json::string(&message)),
+            ("lane", json::lane(&lane)),
+        ]);
+        if writeln!(out, "{reply}").is_err() {
+            break;
+        }
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::*;
+// What a command did, for whichever front end ran it.
+enum Reply {
+    Exit,
+    // Start over on a clean screen.
+    Clear,
+    // The lane changed; any note goes with the redrawn lane.
+    Redraw(String),
+    // Nothing changed.
+    Message(String),
+    Error(String),
+}
 
-    fn mock(rolls: &[T]) -> Game {
-        let mut g = Game::new();
-        for r in rolls {
-            let _ = g.roll(r);
+fn command(lane: &mut Lane, input: &str) -> Reply {
+    let (cmd, arg) = match input.split_once(' ') {
+        Some((cmd, arg)) => (cmd, arg.trim()),
+        None => (input, ""),
+    };
+    let changed = |result: Result<String, String>| match result {
+        Ok(note) => Reply::Redraw(note),
+        Err(e) => Reply::Error(e),
+    };
+    match (cmd, arg) {
+        ("exit", "") => Reply::Exit,
+        ("restart", "") => {
+            lane.restart();
+            Reply::Clear
+        }
+        ("undo", "") => {
+            lane.undo();
+            Reply::Redraw("".to_string())
+        }
+        ("undo", name) => match lane.position(name) {
+            Some(i) => {
+                lane.undo_bowler(i);
+                Reply::Redraw("".to_string())
+            }
+            None => Reply::Error("Unknown bowler!".to_string()),
+        },
+        ("redo", "") => {
+            lane.redo();
+            Reply::Redraw("".to_string())
+        }
+        ("roll", args) if !args.is_empty() => {
+            changed(roll(lane, args).map(|()| "".to_string()))
+        }
+        ("leave", args) if !args.is_empty() => changed(leave(lane, args)),
+        ("odds", "") => Reply::Message(chances(lane)),
+        ("target", args) if !args.is_empty() => {
+            Reply::Message(target(lane, args))
+        }
+        ("edit", args) => changed(edit(lane, args)),
+        ("save", path) if !path.is_empty() => {
+            match fs::write(path, save::save(lane)) {
+                Err(e) => Reply::Error(e.to_string()),
+                Ok(()) => Reply::Message(format!("Saved {path}")),
+            }
+        }
+        ("export", path) if !path.is_empty() => {
+            match fs::write(path, export(lane, path)) {
+                Err(e) => Reply::Error(e.to_string()),
+                Ok(()) => Reply::Message(format!("Exported {path}")),
+            }
+        }
+        ("load", path) if !path.is_empty() => {
+            let loaded = fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|s| save::load(&s).map_err(|e| e.to_string()));
+            changed(loaded.map(|loaded| {
+                *lane = loaded;
+                "".to_string()
+            }))
         }
-        g
+        // new rules mean new games, so not with rolls to lose
+        ("rules", rules) if !rules.is_empty() && !lane.history().is_empty() => {
+            Reply::Error("Restart before changing the rules!".to_string())
+        }
+        ("rules", rules) if !rules.is_empty() => {
+            changed(rules.parse::<Rules>().map_err(|e| e.to_string()).map(
+                |rules| {
+                    let names = lane.bowlers().iter().map(|b| b.name());
+                    let names = names.collect::<Vec<_>>();
+                    *lane = Lane::with_bowlers(&names, rules).unwrap();
+                    "".to_string()
+                },
+            ))
+        }
+        ("add", name) if !name.is_empty() => changed(
+            lane.add(name)
+                .map(|()| "".to_string())
+                .map_err(|e| e.to_string()),
+        ),
+        ("clear", "") => Reply::Clear,
+        // the pins are put back and the ball bowled again
+        ("dead", "") => changed(
+            lane.deliver(&Delivery::DeadBall)
+                .map(|()| "Dead ball".to_string())
+                .map_err(|e| e.to_string()),
+        ),
+        _ => changed(roll(lane, input).map(|()| "".to_string())),
     }
+}
 
-    fn score(rolls: &[T]) -> T {
-        mock(rolls).scores().sum()
+// `<frame> <roll> <pins> [name]`, defaulting to whoever rolled last.
+// Rolls that no longer fit are dropped, and listed in the note.
+fn edit(lane: &mut Lane, args: &str) -> Result<String, String> {
+    let args = args.split_whitespace().collect::<Vec<_>>();
+    let (frame, roll, p, name) = match &args[..] {
+        [frame, roll, p] => (frame, roll, p, None),
+        [frame, roll, p, name] => (frame, roll, p, Some(name)),
+        _ => return Err("Usage: edit <frame> <roll> <pins> [name]".to_string()),
+    };
+    let (Ok(frame @ 1..), Ok(roll @ 1..), Ok(p)) =
+        (frame.parse::<usize>(), roll.parse::<usize>(), p.parse())
+    else {
+        return Err("Invalid input!".to_string());
+    };
+    let b = match name {
+        Some(name) => match lane.position(name) {
+            Some(b) => b,
+            None => return Err("Unknown bowler!".to_string()),
+        },
+        None => lane.last(),
+    };
+    let (invalidated, dead_balls) = lane
+        .edit(b, frame - 1, roll - 1, &p)
+        .map_err(|e| e.to_string())?;
+    let mut dropped = invalidated
+        .iter()
+        .map(|x| {
+            let (frame, roll) = (x.frame + 1, x.roll + 1);
+            format!("Dropped {frame}.{roll} ({}): {}", x.pins, x.error)
+        })
+        .collect::<Vec<_>>();
+    if dead_balls > 0 {
+        dropped.push(format!("Dropped {dead_balls} dead ball(s)"));
     }
+    Ok(dropped.join("\n"))
+}
 
-    fn mock_str(rolls: &str) -> Game {
-        let mut g = Game::new();
-        for s in rolls.split_whitespace() {
-            let r = s.parse().unwrap();
-            let _ = g.roll(&r);
+// Each bowler's expected score, and their chance of beating everyone else's,
+// from a model of how they have bowled so far.
+fn chances(lane: &Lane) -> String {
+    let dists = lane
+        .bowlers()
+        .iter()
+        .map(|b| {
+            let model = Model::learn(lane.rules(), &[b.game()]);
+            odds::distribution(b.game(), &model)
+        })
+        .collect::<Vec<_>>();
+    let mut lines = vec![];
+    for (i, b) in lane.bowlers().iter().enumerate() {
+        let expected = odds::mean(&dists[i]);
+        let mut line = format!("{}: expect {expected:.1}", b.name());
+        for (j, o) in lane.bowlers().iter().enumerate().filter(|&(j, _)| j != i)
+        {
+            let p = odds::win(&dists[i], &dists[j]) * 100.0;
+            line += &format!(", {p:.1}% to beat {}", o.name());
         }
-        g
-    }
-
-    fn score_str(rolls: &str) -> T {
-        mock_str(rolls).scores().sum()
+        lines.push(line);
     }
+    lines.join("\n")
+}
 
-    fn pad(last_frame: &[T]) -> Game {
-        let mut g = mock(&[0; LAST_FRAME * 2]);
-        for p in last_frame {
-            let _ = g.roll(p);
+// `<score> [name]`, for whoever's turn it is by default.
+fn target(lane: &Lane, args: &str) -> String {
+    let (score, name) = match args.split_once(' ') {
+        Some((score, name)) => (score, Some(name.trim())),
+        None => (args, None),
+    };
+    let Ok(score) = score.parse() else {
+        return "Invalid input!".to_string();
+    };
+    let b = match name {
+        Some(name) => match lane.position(name) {
+            Some(b) => b,
+            None => return "Unknown bowler!".to_string(),
+        },
+        None => lane.turn().unwrap_or(lane.last()),
+    };
+    match lane.bowlers()[b].game().needed(score) {
+        None => format!("{score} is out of reach"),
+        Some(rolls) if rolls.is_empty() => format!("{score} is certain"),
+        Some(rolls) => {
+            let rolls = rolls.iter().map(|p| p.to_string());
+            let rolls = rolls.collect::<Vec<_>>().join(" ");
+            format!("{score} needs {rolls} then anything")
         }
-        g
     }
+}
 
-    // #[test]
-    // fn test_txt() {
-    //     let input = include_str!("./input.txt");
-    //     let ans = input
-    //         .lines()
-    //         .map(score_str)
-    //         .map(|x| x.to_string() + "\r\n")
-    //         .collect::<String>();
-    //     assert!(std::fs::write("./ans.txt", ans).is_ok());
-    // }
-
-    #[test]
-    fn test_streaks() {
-        assert_eq!(0, score(&[0; 20 + 1]));
-        assert_eq!(20, score(&[1; 20 + 1]));
-        assert_eq!(40, score(&[2; 20 + 1]));
-        assert_eq!(60, score(&[3; 20 + 1]));
-        assert_eq!(80, score(&[4; 20 + 1]));
-        assert_eq!(150, score(&[5; 21 + 1]));
-        assert_eq!(300, score(&[10; 12 + 1]));
+// `<pins standing> [count]`, e.g. `leave 7-10` for a first ball of 8. A
+// split or named leave comes back as a note.
+fn leave(lane: &mut Lane, args: &str) -> Result<String, String> {
+    let (leave, count) = match args.split_once(' ') {
+        Some((leave, count)) => (leave, Some(count.trim())),
+        None => (args, None),
+    };
+    let leave = match leave.parse::<Leave>() {
+        Ok(leave) if leave.fits(lane.rules()) => leave,
+        Ok(_) => return Err("Invalid leave!".to_string()),
+        Err(e) => return Err(e.to_string()),
+    };
+    let p = match count.map(str::parse) {
+        None => leave.knocked(lane.rules()),
+        Some(Ok(p)) => p,
+        Some(Err(_)) => return Err("Invalid input!".to_string()),
+    };
+    lane.roll_leave(&p, &leave).map_err(|e| e.to_string())?;
+    if lane.rules().pins() == 10 {
+        Ok(describe(&leave))
+    } else {
+        Ok("".to_string())
     }
+}
 
-    #[test]
-    fn test_open_frames() {
-        // frame 1     2     3     4     5     6     7     8     9     10
-        let a = "4  5  5  4  5  4  5  4  5  4  5  4  5  4  5  4  5  4  5  4";
-        let b = "1  2  3  4  5  4  3  2  1  0  9  0  0  9  9  0  0  1  0  0";
-        let c = "0  2  0  2  0  2  0  2  0  2  0  2  0  2  0  2  0  2  0  2";
-        let d = "3  0  3  0  3  0  3  0  3  0  3  0  3  0  3  0  3  0  3  0";
-        assert_eq!(90, score_str(a));
-        assert_eq!(53, score_str(b));
-        assert_eq!(20, score_str(c));
-        assert_eq!(30, score_str(d));
+// Either a pin count, or scoresheet marks such as `X`, `7/` or `81`, read
+// the same way as `score -n`. Marks go in all together or not at all, and
+// digits that are neither a pin count nor marks are a bad pin count.
+fn roll(lane: &mut Lane, input: &str) -> Result<(), String> {
+    let rack = lane.rules().rack();
+    if let Some(p) = input.parse().ok().filter(|&p| p <= rack) {
+        return lane.roll(&p).map_err(|e| e.to_string());
     }
-
-    #[test]
-    fn test_spares() {
-        // frame 1     2     3     4     5     6     7     8     9     10
-        let a = "6  4  5  5  6  4  7  3  1  9  2  8  6  4  5  5  7  3  6  4  4";
-        assert_eq!(149, score_str(a));
+    if input.is_empty() {
+        return Err("Invalid input!".to_string());
     }
+    let mut next = lane.clone();
+    for c in input.chars().filter(|c| !c.is_whitespace()) {
+        if let Err(e) = next.roll_mark(c) {
+            if input.chars().all(|c| c.is_ascii_digit()) {
+                return Err("Invalid # of pins!".to_string());
+            }
+            return Err(e.to_string());
+        }
+    }
+    *lane = next;
+    Ok(())
+}
 
-    #[test]
-    fn test_strikes() {
-        assert_eq!(25, score(&[10, 5, 0, 5, 0]));
-        assert_eq!(31, score(&[10, 5, 2, 5, 2]));
-        assert_eq!(23, score(&[5, 0, 10, 2, 2]));
-        assert_eq!(40, score(&[10, 10, 2, 2]));
-        assert_eq!(70, score(&[10, 10, 10, 2, 2]));
-        assert_eq!(100, score(&[10, 10, 10, 10, 2, 2]));
+// `None` once input runs out.
+fn user_input() -> Option<String> {
+    let mut buffer = String::new();
+    match std::io::stdin().read_line(&mut buffer).unwrap() {
+        0 => None,
+        _ => Some(buffer.trim().to_string()),
     }
+}
 
-    #[test]
-    fn test_spares_and_strikes() {
-        // strike before spare
-        assert_eq!(32, score(&[10, 5, 5, 2]));
-        assert_eq!(36, score(&[10, 5, 5, 2, 2]));
-        assert_eq!(58, score(&[10, 5, 5, 10, 2, 2]));
-        assert_eq!(60, score(&[10, 5, 5, 10, 5, 5]));
-        assert_eq!(72, score(&[10, 5, 5, 10, 5, 5, 2]));
-        assert_eq!(61, score(&[10, 10, 5, 10, 5, 2, 2]));
+#[cfg(test)]
+mod tests {
+    use crate::*;
 
-        // spare before strike
-        assert_eq!(40, score(&[5, 5, 10, 5, 5]));
-        assert_eq!(52, score(&[5, 5, 10, 5, 5, 2]));
+    fn script_lines(input: &str) -> Vec<String> {
+        let mut out = vec![];
+        script(input.as_bytes(), &mut out);
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
     }
 
     #[test]
-    fn test_last_frame() {
-        let f = |x: &[T]| pad(x).scores().sum::<T>();
-
-        // last frame score = sum(frame)
-        assert_eq!(20, f(&[10, 6, 4]));
-        assert_eq!(15, f(&[5, 5, 5]));
-        assert_eq!(15, f(&[1, 9, 5]));
-        assert_eq!(15, f(&[10, 0, 5]));
-        assert_eq!(24, f(&[10, 10, 4]));
-        assert_eq!(20, f(&[5, 5, 10]));
-
-        // extra rolls after spare/strike
-        assert_eq!(20, f(&[10, 6, 4, 1]));
-        assert_eq!(24, f(&[10, 10, 4, 1]));
-        assert_eq!(15, f(&[5, 5, 5, 1]));
-        assert_eq!(15, f(&[1, 9, 5, 1]));
-        assert_eq!(15, f(&[10, 0, 5, 1]));
-        assert_eq!(20, f(&[5, 5, 10, 1]));
-
-        // extra rolls after open frame
-        assert_eq!(9, f(&[4, 5, 5, 1]));
-        assert_eq!(5, f(&[5, 0, 5, 1]));
-        assert_eq!(0, f(&[0, 0, 5, 1]));
-
-        // invalid inputs
-        assert_eq!(1, f(&[1, 10, 0, 1, 1]));
-        assert_eq!(2, f(&[1, 10, 1, 1, 1]));
-        assert_eq!(11, f(&[5, 10, 5, 1, 1, 1]));
-    }
+    fn test_script() {
+        let lines = script_lines("roll 10\n\nroll 7\nX\nundo\nexit\nroll 1");
+        assert_eq!(4, lines.len());
+        assert!(lines[0].starts_with(
+            r#"{"command":"roll 10","ok":true,"message":"","lane":{"#
+        ));
+        assert!(lines[0].contains(r#""frames":[[10],[]]"#));
+        assert!(lines[1].contains(r#""frames":[[10],[7]]"#));
+        assert!(lines[2].starts_with(
+            r#"{"command":"X","ok":false,"message":"Invalid # of pins! (X)""#
+        ));
+        assert!(lines[3].contains(r#""frames":[[10],[]]"#));
 
-    #[test]
-    fn test_undo() {
-        fn f(p: T, rolls: impl Iterator<Item = T>) {
-            let mut a = Game::new();
-            let mut b = Game::new();
-            for r in rolls {
-                let _ = a.roll(&r);
-                let _ = b.roll(&r);
-            }
-            if b.roll(&p).is_ok() {
-                b.undo();
-            };
-            assert_eq!(a.scores().sum::<T>(), b.scores().sum());
-        }
+        // stops at input that isn't text
+        let mut out = vec![];
+        script(&b"roll 3\nroll \xff\nroll 4\n"[..], &mut out);
+        assert_eq!(1, String::from_utf8(out).unwrap().lines().count());
 
-        for p in 0..=11 {
-            for x in 0..=10 {
-                for y in 0..=30 {
-                    f(p, std::iter::repeat(x).take(y));
-                }
-            }
-        }
+        let lines = script_lines("add a\nadd b\nrestart\nroll 10\nundo c");
+        assert!(lines[1].contains(r#""name":"b""#));
+        assert!(lines[3].contains(r#""turn":1"#));
+        assert!(lines[4].contains(r#""ok":false,"message":"Unknown bowler!""#));
     }
 
     #[test]
-    fn test_normal_frame_to_string() {
-        let f = |x: &[T]| mock(x).frame_to_string(0);
-
-        // strike
-        assert_eq!(" X ".to_string(), f(&[10, 0]));
-
-        // spare
-        assert_eq!("0,/".to_string(), f(&[0, 10]));
-        assert_eq!("4,/".to_string(), f(&[4, 6]));
-        assert_eq!("5,/".to_string(), f(&[5, 5]));
-        assert_eq!("6,/".to_string(), f(&[6, 4]));
-
-        // open frame
-        assert_eq!("".to_string(), f(&[]));
-        assert_eq!("0,".to_string(), f(&[0]));
-        assert_eq!("1,".to_string(), f(&[1]));
-        assert_eq!("1,2".to_string(), f(&[1, 2]));
-        assert_eq!("0,0".to_string(), f(&[0, 0]));
-        assert_eq!("0,4".to_string(), f(&[0, 4]));
-        assert_eq!("4,0".to_string(), f(&[4, 0]));
+    fn test_roll() {
+        let mut lane = Lane::new();
+        for bad in ["55", "99", "56"] {
+            let e = Err("Invalid # of pins!".to_string());
+            assert_eq!(e, roll(&mut lane, bad), "{bad}");
+        }
+        assert!(roll(&mut lane, "9/Z").is_err());
+        assert!(lane.history().is_empty());
+        // marks, as `score -n` reads them
+        assert_eq!(Ok(()), roll(&mut lane, "81"));
+        assert_eq!(Ok(()), roll(&mut lane, "9/ 8 1"));
+        assert_eq!(6, lane.history().len());
+        let opts = batch::Options {
+            notation: true,
+            ..Default::default()
+        };
+        let (game, _) = batch::play_line(1, "81 9/ 8 1", &opts);
+        assert_eq!(&game, lane.bowlers()[0].game());
     }
 
     #[test]
-    fn test_last_frame_to_string() {
-        let f = |x: &[T]| pad(x).frame_to_string(LAST_FRAME);
-
-        // 1 roll
-        assert_eq!("1,".to_string(), f(&[1]));
-        assert_eq!("2,".to_string(), f(&[2]));
-        assert_eq!("3,".to_string(), f(&[3]));
-        assert_eq!("4,".to_string(), f(&[4]));
-        assert_eq!("5,".to_string(), f(&[5]));
-        assert_eq!("6,".to_string(), f(&[6]));
-        assert_eq!("7,".to_string(), f(&[7]));
-        assert_eq!("8,".to_string(), f(&[8]));
-        assert_eq!("9,".to_string(), f(&[9]));
-        assert_eq!("X,".to_string(), f(&[10]));
-
-        // 2 rolls
-        assert_eq!("0,0".to_string(), f(&[0, 0]));
-        assert_eq!("9,0".to_string(), f(&[9, 0]));
-        assert_eq!("9,/,".to_string(), f(&[9, 1]));
-        assert_eq!("1,/,".to_string(), f(&[1, 9]));
-        assert_eq!("X,0,".to_string(), f(&[10, 0]));
-        assert_eq!("X,5,".to_string(), f(&[10, 5]));
-        assert_eq!("X,X,".to_string(), f(&[10, 10]));
-
-        // 3 rolls with strikes
-        assert_eq!("X,0,0".to_string(), f(&[10, 0, 0]));
-        assert_eq!("0,/,0".to_string(), f(&[0, 10, 0]));
-        assert_eq!("0,0".to_string(), f(&[0, 0, 0]));
-        assert_eq!("0,0".to_string(), f(&[0, 0, 10]));
-        assert_eq!("X,X,0".to_string(), f(&[10, 10, 0]));
-        assert_eq!("0,/,X".to_string(), f(&[0, 10, 10]));
-        assert_eq!("X,X,X".to_string(), f(&[10, 10, 10]));
-
-        // 3 rolls with spares
-        assert_eq!("5,/,5".to_string(), f(&[5, 5, 5]));
-        assert_eq!("5,/,0".to_string(), f(&[5, 5, 0]));
-        assert_eq!("5,0".to_string(), f(&[5, 0, 0]));
-        assert_eq!("4,4".to_string(), f(&[4, 4, 4]));
-        assert_eq!("4,/,4".to_string(), f(&[4, 6, 4]));
-        assert_eq!("6,/,X".to_string(), f(&[6, 4, 10]));
-        assert_eq!("4,/,X".to_string(), f(&[4, 6, 10]));
+    fn test_rules() {
+        let lines =
+            script_lines("roll 7\nrules five-pin\nrestart\nrules five-pin");
+        assert!(lines[1].contains(
+            r#""ok":false,"message":"Restart before changing the rules!""#
+        ));
+        assert!(lines[1].contains(r#""frames":[[7]]"#));
+        assert!(lines[3].contains(r#""ok":true"#));
     }
 }
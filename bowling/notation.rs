@@ -0,0 +1,138 @@
+use crate::{Delivery, Game, RollError, T};
+use std::{fmt, str::FromStr};
+
+pub const STRIKE: char = 'X';
+pub const SPARE: char = '/';
+pub const GUTTER: char = '-';
+pub const FOUL: char = 'F';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotationError {
+    InvalidMark(char),
+    Rejected(char, RollError),
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NotationError::InvalidMark(c) => write!(f, "Invalid mark! ({c})"),
+            NotationError::Rejected(c, e) => write!(f, "{e} ({c})"),
+        }
+    }
+}
+
+impl std::error::Error for NotationError {}
+
+// What `mark` would be as the next roll of `game`.
+pub fn delivery(mark: char, game: &Game) -> Option<Delivery> {
+    match mark.to_ascii_uppercase() {
+        FOUL => Some(Delivery::Foul),
+        _ => pins(mark, game).map(Delivery::Pins),
+    }
+}
+
+// Pins knocked down by `mark` if it were the next roll of `game`. A digit
+// that would clear the rest of the rack is no mark; that is a spare, `/`.
+pub fn pins(mark: char, game: &Game) -> Option<T> {
+    match mark.to_ascii_uppercase() {
+        STRIKE => Some(game.rules().rack()),
+        GUTTER | FOUL => Some(0),
+        SPARE if !game.fresh_rack() => Some(game.remaining_pins()),
+        c => c.to_digit(10).filter(|&p| {
+            game.fresh_rack() || p == 0 || p != game.remaining_pins()
+        }),
+    }
+}
+
+impl Game {
+    pub fn roll_mark(&mut self, mark: char) -> Result<(), NotationError> {
+        let d = delivery(mark, self).ok_or(NotationError::InvalidMark(mark))?;
+        self.deliver(&d)
+            .map_err(|e| NotationError::Rejected(mark, e))
+    }
+}
+
+impl FromStr for Game {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut game = Game::default();
+        for c in s.chars().filter(|c| !c.is_whitespace()) {
+            game.roll_mark(c)?;
+        }
+        Ok(game)
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let frames = (0..self.frames().len())
+            .map(|i| self.frame_to_string(i))
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>();
+        write!(f, "{}", frames.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::notation::*;
+    use crate::Frame;
+    use rng::Rng;
+
+    fn rolls(g: &Game) -> Vec<T> {
+        g.frames().iter().flat_map(Frame::rolls).copied().collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let g = "X 7/ 9- 81 X X X 9/ -- XXX".parse::<Game>().unwrap();
+        assert_eq!(
+            vec![10, 7, 3, 9, 0, 8, 1, 10, 10, 10, 9, 1, 0, 0, 10, 10, 10],
+            rolls(&g)
+        );
+        assert_eq!(176, g.total());
+        assert_eq!(Ok(vec![0, 0, 0]), "F-f".parse().map(|g| rolls(&g)));
+        assert_eq!(Ok(vec![10, 3, 7]), "x3/".parse().map(|g| rolls(&g)));
+
+        assert_eq!(Err(NotationError::InvalidMark('/')), "/".parse::<Game>());
+        assert_eq!(Err(NotationError::InvalidMark('/')), "X/".parse::<Game>());
+        assert_eq!(Err(NotationError::InvalidMark('?')), "1?".parse::<Game>());
+        assert_eq!(Err(NotationError::InvalidMark('5')), "55".parse::<Game>());
+        assert_eq!(
+            Err(NotationError::Rejected(
+                'X',
+                RollError::TooManyPins {
+                    remaining: 9,
+                    attempted: 10
+                }
+            )),
+            "1X".parse::<Game>()
+        );
+    }
+
+    #[test]
+    fn test_format() {
+        let f = |x: &str| x.parse::<Game>().unwrap().to_string();
+        assert_eq!("", f(""));
+        assert_eq!("X 7/ 9- 81 X X X 9/ -- XXX", f("X7/9-81XXX9/--XXX"));
+        assert_eq!("-/ -1 1", f("0/-11"));
+        assert_eq!("FF FF FF FF FF FF FF FF FF FF", f(&"F".repeat(20)));
+        assert_eq!("F/ -F", f("F/-f"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = Rng::new(123);
+        for _ in 0..1000 {
+            let mut g = Game::default();
+            for _ in 0..25 {
+                let _ = g.roll(&(rng.range(0..11) as T));
+            }
+            let parsed = g.to_string().parse::<Game>().unwrap();
+            assert_eq!(rolls(&g), rolls(&parsed));
+            assert_eq!(g.total(), parsed.total());
+            assert_eq!(g.to_string(), parsed.to_string());
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use crate::{Frame, Game, Rules, T};
+use std::collections::HashMap;
+use std::ops::{Add, Mul};
+
+// Chance of each pinfall `k` when the standing pins are worth `s`, as
+// `balls[s][k]`. Pinfall the rules can't produce is never rolled, and the
+// rest of that ball's odds are scaled up to make up for it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Model {
+    balls: Vec<Vec<f64>>,
+}
+
+impl Model {
+    // `balls[s]` must have `s + 1` odds for every standing value `s` up to
+    // the rack.
+    pub fn new(rules: &Rules, balls: Vec<Vec<f64>>) -> Option<Model> {
+        let valid = balls.len() == rules.rack() as usize + 1
+            && balls.iter().enumerate().all(|(s, b)| b.len() == s + 1);
+        valid.then_some(Model { balls })
+    }
+
+    // Clears a full rack with chance `strike`, and whatever is left of it
+    // with chance `spare`. Every other possible pinfall is equally likely.
+    pub fn from_rates(rules: &Rules, strike: f64, spare: f64) -> Option<Model> {
+        if !(0.0..=1.0).contains(&strike) || !(0.0..=1.0).contains(&spare) {
+            return None;
+        }
+        let rack = rules.rack() as usize;
+        let balls = (0..=rack)
+            .map(|s| {
+                let clear = if s == rack { strike } else { spare };
+                let misses = (0..s).filter(|&k| rules.reachable(k as T));
+                let miss = (1.0 - clear) / misses.count().max(1) as f64;
+                let odds = (0..s)
+                    .map(|k| miss * rules.reachable(k as T) as usize as f64);
+                odds.chain([clear]).collect()
+            })
+            .collect();
+        Model::new(rules, balls)
+    }
+
+    // Every possible pinfall equally likely.
+    pub fn uniform(rules: &Rules) -> Model {
+        Model::learn(rules, &[])
+    }
+
+    // Counts every roll in `games`, plus one of each possible pinfall so
+    // that nothing is ruled out by a small sample.
+    pub fn learn(rules: &Rules, games: &[&Game]) -> Model {
+        let rack = rules.rack() as usize;
+        let mut counts = (0..=rack)
+            .map(|s| {
+                let reachable = |k| rules.reachable(k as T);
+                (0..=s).map(|k| reachable(k) as usize as f64).collect()
+            })
+            .collect::<Vec<Vec<f64>>>();
+        for game in games {
+            let mut replay = Game::new(rules.clone());
+            for &p in game.frames().iter().flat_map(Frame::rolls) {
+                let s = replay.remaining_pins() as usize;
+                if replay.roll(&p).is_ok() {
+                    counts[s][p as usize] += 1.0;
+                }
+            }
+        }
+        Model { balls: counts }
+    }
+
+    // The pinfall at `u` in [0, 1) along the odds of the next roll.
+    pub fn sample(&self, game: &Game, u: f64) -> T {
+        let odds = self.next(game);
+        let mut seen = 0.0;
+        for &(k, q) in &odds {
+            seen += q;
+            if u < seen {
+                return k;
+            }
+        }
+        odds.last().unwrap().0
+    }
+
+    // Odds of each pinfall from the next roll of `game`, or a sure gutter if
+    // the model allows nothing the rules do.
+    fn next(&self, game: &Game) -> Vec<(T, f64)> {
+        let s = game.remaining_pins() as usize;
+        let odds = self.balls[s].iter().enumerate();
+        let valid = odds
+            .map(|(k, &q)| (k as T, q))
+            .filter(|&(k, q)| q > 0.0 && game.valid(&k).is_ok())
+            .collect::<Vec<_>>();
+        if valid.is_empty() {
+            return vec![(0, 1.0)];
+        }
+        let sum = valid.iter().map(|(_, q)| q).sum::<f64>();
+        valid.into_iter().map(|(k, q)| (k, q / sum)).collect()
+    }
+}
+
+// Chance of each final score, indexed by score.
+pub fn distribution(game: &Game, model: &Model) -> Vec<f64> {
+    finishes(game, 1.0, |g| model.next(g))
+}
+
+// Every way `game` can finish, weighted and summed by final score. `next`
+// gives the rolls that can follow a game, each with its weight. Games that
+// reach the same frame with the same unscored rolls are merged, since only
+// their scored totals differ from then on.
+pub(crate) fn finishes<W, I>(
+    game: &Game,
+    one: W,
+    next: impl Fn(&Game) -> I,
+) -> Vec<W>
+where
+    W: Copy + Default + Add<Output = W> + Mul<Output = W>,
+    I: IntoIterator<Item = (T, W)>,
+{
+    let mut done = vec![W::default(); game.projection().0 as usize + 1];
+    let mut states = HashMap::new();
+    states.insert(key(game), (game.clone(), shifted(&[one], game.total())));
+    while !states.is_empty() {
+        let mut next_states = HashMap::<_, (Game, Vec<W>)>::new();
+        for (_, (game, totals)) in states {
+            if game.is_over() {
+                add(&mut done, &totals, one);
+                continue;
+            }
+            for (k, q) in next(&game) {
+                let mut g = game.clone();
+                g.roll(&k).unwrap();
+                let gained = g.total() - game.total();
+                let totals = shifted(&totals, gained);
+                match next_states.get_mut(&key(&g)) {
+                    Some((_, t)) => add(t, &totals, q),
+                    None => {
+                        let t = totals.iter().map(|&x| x * q).collect();
+                        next_states.insert(key(&g), (g, t));
+                    }
+                }
+            }
+        }
+        states = next_states;
+    }
+    done
+}
+
+// The frame being bowled and every roll that still has to be scored.
+fn key(game: &Game) -> (usize, Vec<Vec<T>>) {
+    let scored = game.scores().count();
+    let frames = game.frames()[scored..].iter().map(|f| f.rolls().to_vec());
+    (game.frames().len(), frames.collect())
+}
+
+fn shifted<W: Copy + Default>(totals: &[W], by: T) -> Vec<W> {
+    let mut t = vec![W::default(); by as usize];
+    t.extend_from_slice(totals);
+    t
+}
+
+fn add<W>(to: &mut Vec<W>, totals: &[W], q: W)
+where
+    W: Copy + Default + Add<Output = W> + Mul<Output = W>,
+{
+    if to.len() < totals.len() {
+        to.resize(totals.len(), W::default());
+    }
+    for (t, &x) in to.iter_mut().zip(totals) {
+        *t = *t + x * q;
+    }
+}
+
+pub fn mean(dist: &[f64]) -> f64 {
+    dist.iter().enumerate().map(|(s, q)| s as f64 * q).sum()
+}
+
+// Chance of finishing above `score`.
+pub fn beat(dist: &[f64], score: T) -> f64 {
+    dist.iter().skip(score as usize + 1).sum()
+}
+
+// Chance that one independent final score is higher than another.
+pub fn win(ours: &[f64], theirs: &[f64]) -> f64 {
+    let mut below = 0.0;
+    let mut win = 0.0;
+    for (s, q) in ours.iter().enumerate() {
+        win += q * below;
+        below += theirs.get(s).copied().unwrap_or(0.0);
+    }
+    win
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::odds::*;
+
+    const EPS: f64 = 1e-9;
+
+    fn mock(rolls: &[T]) -> Game {
+        let mut g = Game::default();
+        for r in rolls {
+            g.roll(r).unwrap();
+        }
+        g
+    }
+
+    // Always `p` on the first ball, then always picks up `q` of the rest.
+    fn fixed(p: usize, q: usize) -> Model {
+        let rules = Rules::default();
+        let balls = (0..=10)
+            .map(|s| {
+                let k = if s == 10 { p } else { q.min(s) };
+                (0..=s).map(|j| (j == k) as usize as f64).collect()
+            })
+            .collect();
+        Model::new(&rules, balls).unwrap()
+    }
+
+    #[test]
+    fn test_fixed() {
+        let d = distribution(&Game::default(), &fixed(10, 0));
+        assert!((d[300] - 1.0).abs() < EPS);
+        let d = distribution(&Game::default(), &fixed(9, 1));
+        assert!((d[190] - 1.0).abs() < EPS);
+        let d = distribution(&mock(&[10; 11]), &fixed(3, 0));
+        assert_eq!(293, d.iter().position(|&q| q > 0.5).unwrap());
+        let d = distribution(&mock(&[10; 12]), &fixed(3, 0));
+        assert!((d[300] - 1.0).abs() < EPS);
+    }
+
+    #[test]
+    fn test_distribution() {
+        // a coin flip between a strike and a gutter on every ball
+        let balls = (0..=10)
+            .map(|s| {
+                let mut b = vec![0.0; s + 1];
+                b[0] = 0.5;
+                b[s] += 0.5;
+                b
+            })
+            .collect();
+        let model = Model::new(&Rules::default(), balls).unwrap();
+        let g = mock(&[0; 18]);
+        let d = distribution(&g, &model);
+        assert!((d.iter().sum::<f64>() - 1.0).abs() < EPS);
+        // -- 1/4, then -/ with a bonus ball and X with two, 1/8 each way
+        assert!((d[0] - 0.25).abs() < EPS);
+        assert!((d[10] - 0.25).abs() < EPS);
+        assert!((d[20] - 0.375).abs() < EPS);
+        assert!((d[30] - 0.125).abs() < EPS);
+        assert!((mean(&d) - 13.75).abs() < EPS);
+        assert!((beat(&d, 10) - 0.5).abs() < EPS);
+        assert!((beat(&d, 30)).abs() < EPS);
+
+        let uniform =
+            distribution(&Game::default(), &Model::uniform(g.rules()));
+        assert!((uniform.iter().sum::<f64>() - 1.0).abs() < EPS);
+        assert_eq!(301, uniform.len());
+        assert!(uniform[300] > 0.0);
+    }
+
+    #[test]
+    fn test_learn() {
+        let rules = Rules::default();
+        let games = [mock(&[10; 12])];
+        let model = Model::learn(&rules, &games.iter().collect::<Vec<_>>());
+        // 12 strikes seen, plus one of each of the 11 outcomes
+        assert!((model.balls[10][10] - 13.0).abs() < EPS);
+        let next = model.next(&Game::default());
+        assert!((next[10].1 - 13.0 / 23.0).abs() < EPS);
+
+        let five_pin = Rules::five_pin();
+        let model = Model::uniform(&five_pin);
+        let next = model.next(&Game::new(five_pin));
+        assert_eq!(14, next.len());
+        assert!(next.iter().all(|&(k, _)| k != 1 && k != 14));
+    }
+
+    #[test]
+    fn test_rates() {
+        let rules = Rules::default();
+        let model = Model::from_rates(&rules, 0.25, 0.5).unwrap();
+        assert_eq!(vec![0.075; 10], model.balls[10][..10]);
+        assert_eq!(0.25, model.balls[10][10]);
+        assert_eq!(vec![0.5, 0.5], model.balls[1]);
+        assert_eq!(None, Model::from_rates(&rules, 1.5, 0.5));
+
+        let g = Game::default();
+        assert_eq!(0, model.sample(&g, 0.0));
+        assert_eq!(9, model.sample(&g, 0.74));
+        assert_eq!(10, model.sample(&g, 0.76));
+        assert_eq!(10, model.sample(&g, 0.9999));
+    }
+
+    #[test]
+    fn test_win() {
+        assert!((win(&[0.0, 1.0], &[1.0]) - 1.0).abs() < EPS);
+        assert!((win(&[1.0], &[1.0])).abs() < EPS);
+        assert!((win(&[0.5, 0.5], &[0.5, 0.5]) - 0.25).abs() < EPS);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::batch::{self, Options};
+use crate::T;
+use std::fmt;
+
+// A second ten-pin scorer, written apart from `Game` to check it. Rolls are
+// first checked against the pins standing, skipping any that don't fit the
+// way the batch scorer does, then the accepted rolls are scored as a flat
+// list: a strike or spare reads its bonus straight from the rolls after it.
+// Frames still waiting on rolls aren't scored.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scored {
+    pub total: T,
+    pub rolls: Vec<T>,
+    // Positions in the line, from 1, of the rolls that were skipped.
+    pub rejected: Vec<usize>,
+}
+
+pub fn score_line(line: &str) -> Scored {
+    let mut rolls = vec![];
+    let mut rejected = vec![];
+    for (i, s) in line.split_whitespace().enumerate() {
+        match s.parse() {
+            Ok(p) if standing(&rolls).is_some_and(|n| p <= n) => rolls.push(p),
+            _ => rejected.push(i + 1),
+        }
+    }
+    Scored {
+        total: total(&rolls),
+        rolls,
+        rejected,
+    }
+}
+
+// Pins standing for the next roll, or `None` once the game is over.
+fn standing(rolls: &[T]) -> Option<T> {
+    let mut i = 0;
+    for _ in 0..9 {
+        match rolls.get(i..i + 2) {
+            _ if rolls.get(i) == Some(&10) => i += 1,
+            Some(_) => i += 2,
+            None => return Some(10 - rolls.get(i).unwrap_or(&0)),
+        }
+    }
+    match rolls[i..] {
+        [] | [10] | [10, 10] => Some(10),
+        [a] => Some(10 - a),
+        [10, b] => Some(10 - b),
+        [a, b] if a + b == 10 => Some(10),
+        _ => None,
+    }
+}
+
+fn total(rolls: &[T]) -> T {
+    let mut total = 0;
+    let mut i = 0;
+    for _ in 0..10 {
+        let (frame, next) = match rolls.get(i..) {
+            Some([10, a, b, ..]) => (10 + a + b, i + 1),
+            Some([a, b, c, ..]) if a + b == 10 => (10 + c, i + 2),
+            Some([a, b, ..]) if a + b < 10 => (a + b, i + 2),
+            _ => break,
+        };
+        total += frame;
+        i = next;
+    }
+    total
+}
+
+// A line the two scorers disagree on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub line: usize,
+    pub input: String,
+    pub game: T,
+    pub reference: T,
+    pub game_rejected: Vec<usize>,
+    pub reference_rejected: Vec<usize>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.input)?;
+        if self.game != self.reference {
+            write!(
+                f,
+                "\n  scored {}, reference {}",
+                self.game, self.reference
+            )?;
+        }
+        if self.game_rejected != self.reference_rejected {
+            write!(
+                f,
+                "\n  skipped rolls {:?}, reference {:?}",
+                self.game_rejected, self.reference_rejected
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Scores every line both ways, under the default rules.
+pub fn verify(input: &str) -> Vec<Mismatch> {
+    let opts = Options::default();
+    let mut mismatches = vec![];
+    for (i, line) in input.lines().enumerate() {
+        let (game, diagnostics) = batch::play_line(i + 1, line, &opts);
+        let reference = score_line(line);
+        let game_rejected = diagnostics.iter().map(|d| d.roll).collect();
+        if game.total() != reference.total
+            || game_rejected != reference.rejected
+        {
+            mismatches.push(Mismatch {
+                line: i + 1,
+                input: line.to_string(),
+                game: game.total(),
+                reference: reference.total,
+                game_rejected,
+                reference_rejected: reference.rejected,
+            });
+        }
+    }
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reference::*;
+    use rng::Rng;
+
+    #[test]
+    fn test_score_line() {
+        assert_eq!(300, score_line(&"10 ".repeat(12)).total);
+        assert_eq!(150, score_line(&"5 ".repeat(21)).total);
+        assert_eq!(0, score_line(&"0 ".repeat(20)).total);
+        assert_eq!(
+            176,
+            score_line("10 7 3 9 0 8 1 10 10 10 9 1 0 0 10 10 10").total
+        );
+        // waiting on the bonus
+        assert_eq!(20, score_line("10 7 3").total);
+        assert_eq!(40, score_line("10 7 3 10").total);
+        assert_eq!(0, score_line("7").total);
+
+        let s = score_line("1 10 0 x 11 9 9");
+        assert_eq!(vec![1, 0, 9], s.rolls);
+        assert_eq!(vec![2, 4, 5, 7], s.rejected);
+        assert_eq!(1, s.total);
+        // nothing after the game is over
+        let s = score_line(&"0 ".repeat(21));
+        assert_eq!(vec![21], s.rejected);
+        let s = score_line("0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 10 5 6 5");
+        assert_eq!(vec![21], s.rejected);
+        assert_eq!(20, s.total);
+        let s = score_line("0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 10 5 5 5");
+        assert_eq!(vec![22], s.rejected);
+    }
+
+    #[test]
+    fn test_verify() {
+        let mut rng = Rng::new(7);
+        let mut input = String::new();
+        for _ in 0..2000 {
+            for _ in 0..25 {
+                input += &format!("{} ", rng.range(0..12));
+            }
+            input += "x\n";
+        }
+        assert_eq!(Vec::<Mismatch>::new(), verify(&input));
+
+        let m = Mismatch {
+            line: 3,
+            input: "10 10".to_string(),
+            game: 30,
+            reference: 20,
+            game_rejected: vec![],
+            reference_rejected: vec![2],
+        };
+        assert_eq!(
+            "line 3: 10 10\n  scored 30, reference 20\n  \
+             skipped rolls [], reference [2]",
+            m.to_string()
+        );
+    }
+}
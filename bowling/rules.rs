@@ -0,0 +1,293 @@
+use crate::T;
+use std::{fmt, str::FromStr};
+
+// Game has `frames` frames.
+// Each frame starts with a full rack of `pin_values`.
+// Max `balls` rolls per frame.
+// A first ball of `no_tap` or more is a strike, else clearing the rack with
+// the second ball is a spare. Both score the rack plus the next
+// `strike_bonus` or `spare_bonus` balls.
+// Last frame only: bonus balls are bowled in the frame itself.
+// Under `Scoring::CurrentFrame` none of the bonus rules apply.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    pub frames: usize,
+    pub balls: usize,
+    pub pin_values: Vec<T>,
+    pub strike_bonus: usize,
+    pub spare_bonus: usize,
+    pub no_tap: T,
+    pub scoring: Scoring,
+}
+
+// World Bowling's current frame scoring has no lookahead: a strike is worth
+// three racks, a spare a rack plus its first ball, and there are never any
+// bonus balls in the last frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scoring {
+    #[default]
+    Traditional,
+    CurrentFrame,
+}
+
+impl fmt::Display for Scoring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Scoring::Traditional => write!(f, "traditional"),
+            Scoring::CurrentFrame => write!(f, "current-frame"),
+        }
+    }
+}
+
+impl FromStr for Scoring {
+    type Err = RulesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "traditional" => Ok(Scoring::Traditional),
+            "current-frame" => Ok(Scoring::CurrentFrame),
+            _ => Err(RulesError::InvalidValue(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RulesError {
+    Unknown(String),
+    InvalidValue(String),
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RulesError::Unknown(s) => write!(f, "Unknown rules! ({s})"),
+            RulesError::InvalidValue(s) => write!(f, "Invalid value! ({s})"),
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::ten_pin()
+    }
+}
+
+impl Rules {
+    pub const PRESETS: [&str; 5] = [
+        "ten-pin",
+        "nine-pin-no-tap",
+        "candlepin",
+        "duckpin",
+        "five-pin",
+    ];
+
+    pub fn ten_pin() -> Rules {
+        Rules {
+            frames: 10,
+            balls: 2,
+            pin_values: vec![1; 10],
+            strike_bonus: 2,
+            spare_bonus: 1,
+            no_tap: 10,
+            scoring: Scoring::Traditional,
+        }
+    }
+
+    // Nine pins on the first ball count as a strike.
+    pub fn nine_pin_no_tap() -> Rules {
+        Rules {
+            no_tap: 9,
+            ..Rules::ten_pin()
+        }
+    }
+
+    pub fn candlepin() -> Rules {
+        Rules {
+            balls: 3,
+            ..Rules::ten_pin()
+        }
+    }
+
+    pub fn duckpin() -> Rules {
+        Rules::candlepin()
+    }
+
+    // Canadian five-pin: pins worth 2-3-5-3-2 for a 15 point rack.
+    pub fn five_pin() -> Rules {
+        Rules {
+            balls: 3,
+            pin_values: vec![2, 3, 5, 3, 2],
+            no_tap: 15,
+            ..Rules::ten_pin()
+        }
+    }
+
+    pub fn preset(name: &str) -> Option<Rules> {
+        match name {
+            "ten-pin" => Some(Rules::ten_pin()),
+            "nine-pin-no-tap" => Some(Rules::nine_pin_no_tap()),
+            "candlepin" => Some(Rules::candlepin()),
+            "duckpin" => Some(Rules::duckpin()),
+            "five-pin" => Some(Rules::five_pin()),
+            _ => None,
+        }
+    }
+
+    pub fn last_frame(&self) -> usize {
+        self.frames - 1
+    }
+
+    pub fn pins(&self) -> usize {
+        self.pin_values.len()
+    }
+
+    // Value of a full rack.
+    pub fn rack(&self) -> T {
+        self.pin_values.iter().sum()
+    }
+
+    // Whether some set of pins is worth exactly `p`.
+    pub fn reachable(&self, p: T) -> bool {
+        if self.pin_values.iter().all(|&v| v == 1) {
+            return p as usize <= self.pins();
+        }
+        let mut sums = vec![false; self.rack() as usize + 1];
+        sums[0] = true;
+        for &v in &self.pin_values {
+            for s in (v as usize..sums.len()).rev() {
+                sums[s] |= sums[s - v as usize];
+            }
+        }
+        sums.get(p as usize).copied().unwrap_or(false)
+    }
+}
+
+// `frames=10 balls=2 pins=1,1,1,1,1,1,1,1,1,1 strike=2 spare=1 no_tap=10
+// scoring=traditional`
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pins = self.pin_values.iter().map(T::to_string);
+        write!(
+            f,
+            "frames={} balls={} pins={} strike={} spare={} no_tap={} \
+             scoring={}",
+            self.frames,
+            self.balls,
+            pins.collect::<Vec<_>>().join(","),
+            self.strike_bonus,
+            self.spare_bonus,
+            self.no_tap,
+            self.scoring
+        )
+    }
+}
+
+// `key=value` pairs on top of ten-pin, or on top of a preset named first,
+// e.g. `five-pin scoring=current-frame`.
+impl FromStr for Rules {
+    type Err = RulesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut kvs = s.split_whitespace().peekable();
+        let mut rules = Rules::ten_pin();
+        let mut no_tap = None;
+        if let Some(preset) = kvs.peek().and_then(|&name| Rules::preset(name)) {
+            kvs.next();
+            no_tap = Some(preset.no_tap);
+            rules = preset;
+        }
+        for kv in kvs {
+            let invalid = || RulesError::InvalidValue(kv.to_string());
+            let (k, v) = kv
+                .split_once('=')
+                .ok_or_else(|| RulesError::Unknown(kv.to_string()))?;
+            match k {
+                "frames" => rules.frames = v.parse().map_err(|_| invalid())?,
+                "balls" => rules.balls = v.parse().map_err(|_| invalid())?,
+                "pins" => {
+                    rules.pin_values = v
+                        .split(',')
+                        .map(|p| p.parse().map_err(|_| invalid()))
+                        .collect::<Result<_, _>>()?
+                }
+                "strike" => {
+                    rules.strike_bonus = v.parse().map_err(|_| invalid())?
+                }
+                "spare" => {
+                    rules.spare_bonus = v.parse().map_err(|_| invalid())?
+                }
+                "no_tap" => no_tap = Some(v.parse().map_err(|_| invalid())?),
+                "scoring" => rules.scoring = v.parse()?,
+                _ => return Err(RulesError::Unknown(kv.to_string())),
+            }
+        }
+        rules.no_tap = no_tap.unwrap_or(rules.rack());
+        let valid = rules.frames > 0
+            && rules.balls > 0
+            && rules.rack() > 0
+            && (1..=rules.rack()).contains(&rules.no_tap);
+        if valid {
+            Ok(rules)
+        } else {
+            Err(RulesError::InvalidValue(s.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rules::*;
+
+    #[test]
+    fn test_parse() {
+        for name in Rules::PRESETS {
+            let rules = name.parse::<Rules>().unwrap();
+            assert_eq!(Ok(rules.clone()), rules.to_string().parse());
+        }
+        assert_eq!(Ok(Rules::ten_pin()), "".parse());
+        assert_eq!(Ok(Rules::nine_pin_no_tap()), "no_tap=9".parse());
+        assert_eq!(
+            "frames=5 balls=2 pins=1,1,1,1,1,1,1,1,1,1 strike=2 spare=1 \
+             no_tap=10 scoring=traditional",
+            "frames=5".parse::<Rules>().unwrap().to_string()
+        );
+        let rules = "pins=1,2 balls=3".parse::<Rules>().unwrap();
+        assert_eq!(3, rules.no_tap);
+        let rules = "nine-pin-no-tap scoring=current-frame".parse::<Rules>();
+        assert_eq!(
+            Ok(Rules {
+                scoring: Scoring::CurrentFrame,
+                ..Rules::nine_pin_no_tap()
+            }),
+            rules
+        );
+
+        let err = |s: &str| RulesError::InvalidValue(s.to_string());
+        assert_eq!(Err(err("frames=x")), "frames=x".parse::<Rules>());
+        assert_eq!(Err(err("frames=0")), "frames=0".parse::<Rules>());
+        assert_eq!(Err(err("no_tap=11")), "no_tap=11".parse::<Rules>());
+        assert_eq!(
+            Err(RulesError::Unknown("bowls=1".to_string())),
+            "bowls=1".parse::<Rules>()
+        );
+        assert_eq!(
+            Err(RulesError::Unknown("lawn".to_string())),
+            "lawn".parse::<Rules>()
+        );
+        assert_eq!(Err(err("golf")), "scoring=golf".parse::<Rules>());
+    }
+
+    #[test]
+    fn test_reachable() {
+        let rules = Rules::five_pin();
+        let reachable = (0..=16).filter(|&p| rules.reachable(p));
+        assert_eq!(
+            vec![0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 15],
+            reachable.collect::<Vec<_>>()
+        );
+        assert!((0..=10).all(|p| Rules::ten_pin().reachable(p)));
+        assert!(!Rules::ten_pin().reachable(11));
+    }
+}
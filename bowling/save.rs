@@ -0,0 +1,298 @@
+use crate::lane::{Lane, LaneError};
+use crate::leave::LeaveError;
+use crate::rules::RulesError;
+use crate::{Delivery, Game, Leave, RollError, Rules};
+use std::fmt;
+
+// The rules if not ten-pin, one line per bowler in turn order, then one line
+// per roll in the order the rolls were made, so undo still works after
+// loading. A first ball entered with its leave is saved as the leave, and
+// fouls and dead balls have lines of their own:
+//
+//     rules frames=10 balls=3 pins=2,3,5,3,2 strike=2 spare=1 no_tap=15 ...
+//     bowler Alice
+//     bowler Bob
+//     roll 10 Alice
+//     leave 7-10 Bob
+//     dead Bob
+//     foul Bob
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    InvalidLine(usize),
+    Rules(usize, RulesError),
+    Leave(usize, LeaveError),
+    UnknownBowler(usize),
+    Lane(usize, LaneError),
+    Rejected(usize, RollError),
+    NoBowlers,
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::InvalidLine(i) => write!(f, "line {i}: Invalid line!"),
+            LoadError::UnknownBowler(i) => {
+                write!(f, "line {i}: Unknown bowler!")
+            }
+            LoadError::Rules(i, e) => write!(f, "line {i}: {e}"),
+            LoadError::Leave(i, e) => write!(f, "line {i}: {e}"),
+            LoadError::Lane(i, e) => write!(f, "line {i}: {e}"),
+            LoadError::Rejected(i, e) => write!(f, "line {i}: {e}"),
+            LoadError::NoBowlers => write!(f, "No bowlers!"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+pub fn save(lane: &Lane) -> String {
+    let mut s = String::new();
+    if lane.rules() != &Rules::default() {
+        s += &format!("rules {}\n", lane.rules());
+    }
+    for b in lane.bowlers() {
+        s += &format!("bowler {}\n", b.name());
+    }
+    let mut rolls = lane
+        .bowlers()
+        .iter()
+        .map(|b| deliveries(b.game()).into_iter())
+        .collect::<Vec<_>>();
+    for &i in lane.history() {
+        let name = lane.bowlers()[i].name();
+        s += &match rolls[i].next().unwrap() {
+            (_, Some(leave)) => format!("leave {leave} {name}\n"),
+            (Delivery::Pins(p), None) => format!("roll {p} {name}\n"),
+            (Delivery::Foul, None) => format!("foul {name}\n"),
+            (Delivery::DeadBall, None) => format!("dead {name}\n"),
+        };
+    }
+    s
+}
+
+// Everything `game` was given, in order, with the leaves of first balls.
+fn deliveries(game: &Game) -> Vec<(Delivery, Option<Leave>)> {
+    let mut deliveries = vec![];
+    for f in game.frames() {
+        for j in 0..=f.len() {
+            let dead = f.dead_balls().iter().filter(|&&k| k == j);
+            deliveries.extend(dead.map(|_| (Delivery::DeadBall, None)));
+            let Some(&p) = f.rolls().get(j) else {
+                continue;
+            };
+            let d = match f.is_foul(j) {
+                true => Delivery::Foul,
+                false => Delivery::Pins(p),
+            };
+            deliveries.push((d, f.leave().filter(|_| j == 0)));
+        }
+    }
+    deliveries
+}
+
+pub fn load(s: &str) -> Result<Lane, LoadError> {
+    let lines = s.lines().map(|l| l.trim_end_matches('\r'));
+    let mut rules = Rules::default();
+    let mut names = vec![];
+    let mut rolls = vec![];
+    for (i, line) in lines.enumerate().filter(|(_, l)| !l.is_empty()) {
+        let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match kind {
+            "rules" if names.is_empty() => {
+                rules = rest.parse().map_err(|e| LoadError::Rules(i + 1, e))?
+            }
+            "bowler" if rolls.is_empty() => names.push((i + 1, rest)),
+            "roll" => {
+                let (p, name) = rest.split_once(' ').unwrap_or((rest, ""));
+                let p = p.parse().map_err(|_| LoadError::InvalidLine(i + 1))?;
+                rolls.push((i + 1, Delivery::Pins(p), None, name));
+            }
+            "foul" => rolls.push((i + 1, Delivery::Foul, None, rest)),
+            "dead" => rolls.push((i + 1, Delivery::DeadBall, None, rest)),
+            "leave" => {
+                let (leave, name) = rest.split_once(' ').unwrap_or((rest, ""));
+                let leave = leave
+                    .parse::<Leave>()
+                    .map_err(|e| LoadError::Leave(i + 1, e))?;
+                if !leave.fits(&rules) {
+                    return Err(LoadError::Rejected(
+                        i + 1,
+                        RollError::InvalidLeave,
+                    ));
+                }
+                let p = Delivery::Pins(leave.knocked(&rules));
+                rolls.push((i + 1, p, Some(leave), name));
+            }
+            _ => return Err(LoadError::InvalidLine(i + 1)),
+        }
+    }
+    // Such as an empty or cut off file; a lane always has a bowler.
+    if names.is_empty() {
+        return Err(LoadError::NoBowlers);
+    }
+    let bowlers = names.iter().map(|&(_, name)| name).collect::<Vec<_>>();
+    let mut lane = Lane::with_bowlers(&bowlers, rules).map_err(|e| {
+        // The line naming the bowler a second time.
+        let LaneError::DuplicateName(name) = &e;
+        let mut lines = names.iter().filter(|(_, n)| n == name);
+        LoadError::Lane(lines.nth(1).map_or(0, |&(i, _)| i), e)
+    })?;
+    for (i, d, leave, name) in rolls {
+        let b = lane.position(name).ok_or(LoadError::UnknownBowler(i))?;
+        match (d, leave) {
+            (Delivery::Pins(p), Some(leave)) => {
+                lane.roll_leave_bowler(b, &p, &leave)
+            }
+            _ => lane.deliver_bowler(b, &d),
+        }
+        .map_err(|e| LoadError::Rejected(i, e))?;
+    }
+    Ok(lane)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::save::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut lane = Lane::new();
+        lane.add("Alice").unwrap();
+        lane.add("Bob Jr").unwrap();
+        for p in [10, 7, 3, 9, 0, 10] {
+            lane.roll(&p).unwrap();
+        }
+        lane.undo_bowler(0);
+        lane.roll(&1).unwrap();
+
+        let s = save(&lane);
+        assert_eq!(
+            "bowler Alice\nbowler Bob Jr\nroll 10 Alice\nroll 7 Bob Jr\n\
+             roll 3 Bob Jr\nroll 9 Alice\nroll 10 Bob Jr\nroll 1 Alice\n",
+            s
+        );
+        let mut loaded = load(&s).unwrap();
+        assert_eq!(s, save(&loaded));
+        assert_eq!(lane.turn(), loaded.turn());
+        loaded.undo();
+        lane.undo();
+        assert_eq!(save(&lane), save(&loaded));
+
+        let mut lane = Lane::new();
+        lane.roll(&4).unwrap();
+        lane.add("Alice").unwrap();
+        let s = save(&lane);
+        assert_eq!("bowler \nbowler Alice\nroll 4 \n", s);
+        assert_eq!(s, save(&load(&s).unwrap()));
+        assert_eq!(s, save(&load(&s.replace(" \n", "\n")).unwrap()));
+    }
+
+    #[test]
+    fn test_rules() {
+        let mut lane = Lane::with_bowlers(&["a"], Rules::five_pin()).unwrap();
+        lane.roll(&15).unwrap();
+        let s = save(&lane);
+        assert_eq!(
+            "rules frames=10 balls=3 pins=2,3,5,3,2 strike=2 spare=1 \
+             no_tap=15 scoring=traditional\nbowler a\nroll 15 a\n",
+            s
+        );
+        assert_eq!(&Rules::five_pin(), load(&s).unwrap().rules());
+        // saved before there was a choice of scoring
+        let old = s.replace(" scoring=traditional", "");
+        assert_eq!(&Rules::five_pin(), load(&old).unwrap().rules());
+        assert_eq!(
+            Err(LoadError::Rules(1, RulesError::Unknown("x".to_string()))),
+            load("rules x").map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_leaves() {
+        let mut lane = Lane::with_bowlers(&["a"], Rules::default()).unwrap();
+        lane.roll_leave(&8, &"7-10".parse().unwrap()).unwrap();
+        lane.roll(&1).unwrap();
+        lane.roll_leave(&10, &"X".parse().unwrap()).unwrap();
+        let s = save(&lane);
+        assert_eq!("bowler a\nleave 7-10 a\nroll 1 a\nleave X a\n", s);
+        assert_eq!(lane, load(&s).unwrap());
+
+        assert_eq!(
+            Err(LoadError::Leave(
+                2,
+                LeaveError::InvalidPin("7-".to_string())
+            )),
+            load("bowler a\nleave 7- a").map(|_| ())
+        );
+        assert_eq!(
+            Err(LoadError::Rejected(2, RollError::InvalidLeave)),
+            load("bowler a\nleave 11 a").map(|_| ())
+        );
+        assert_eq!(
+            Err(LoadError::Rejected(3, RollError::InvalidLeave)),
+            load("bowler a\nroll 1 a\nleave 10 a").map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_fouls() {
+        let mut lane = Lane::with_bowlers(&["a"], Rules::default()).unwrap();
+        lane.deliver(&Delivery::DeadBall).unwrap();
+        lane.deliver(&Delivery::Foul).unwrap();
+        lane.roll(&4).unwrap();
+        lane.deliver(&Delivery::DeadBall).unwrap();
+        let s = save(&lane);
+        assert_eq!("bowler a\ndead a\nfoul a\nroll 4 a\ndead a\n", s);
+        assert_eq!(lane, load(&s).unwrap());
+    }
+
+    #[test]
+    fn test_load_errors() {
+        assert_eq!(
+            Err(LoadError::InvalidLine(1)),
+            load("player a").map(|_| ())
+        );
+        assert_eq!(Err(LoadError::NoBowlers), load("").map(|_| ()));
+        assert_eq!(
+            Err(LoadError::NoBowlers),
+            load("rules five-pin\n").map(|_| ())
+        );
+        assert_eq!(
+            Err(LoadError::InvalidLine(3)),
+            load("bowler a\nroll 1 a\nbowler b").map(|_| ())
+        );
+        assert_eq!(
+            Err(LoadError::InvalidLine(2)),
+            load("bowler a\nroll x a").map(|_| ())
+        );
+        assert_eq!(
+            Err(LoadError::UnknownBowler(2)),
+            load("bowler a\nroll 1 b").map(|_| ())
+        );
+        assert_eq!(
+            Err(LoadError::Lane(
+                2,
+                LaneError::DuplicateName("a".to_string())
+            )),
+            load("bowler a\nbowler a").map(|_| ())
+        );
+        assert_eq!(
+            Err(LoadError::Lane(
+                5,
+                LaneError::DuplicateName("a".to_string())
+            )),
+            load("rules ten-pin\n\nbowler a\nbowler b\nbowler a").map(|_| ())
+        );
+        assert_eq!(
+            Err(LoadError::Rejected(
+                3,
+                RollError::TooManyPins {
+                    remaining: 5,
+                    attempted: 6
+                }
+            )),
+            load("bowler a\nroll 5 a\nroll 6 a").map(|_| ())
+        );
+    }
+}
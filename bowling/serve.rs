@@ -0,0 +1,239 @@
+use crate::{command, Reply};
+use bowling::json;
+use bowling::lane::Lane;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// Commands that would touch the server's files.
+const LOCAL: [&str; 3] = ["save", "load", "export"];
+
+// Every lane's scores, shared by all connections and only kept in memory.
+// Each line from a client is `<lane> <command>`, lanes counting from 1, with
+// the REPL's commands apart from those in `LOCAL`. It is answered with a
+// JSON line, and every display that sent `watch` gets the lane again if it
+// changed. A new display gets every lane straight away. `exit` closes the
+// connection once answered, leaving the lanes as they are:
+//
+//     > 2 add Alice
+//     < {"lane":2,"command":"add Alice","ok":true,"message":""}
+//     > watch
+//     < {"lane":1,"state":{"rules":...}}
+//     < {"lane":2,"state":{"rules":...}}
+pub struct Server {
+    lanes: Vec<Lane>,
+    watchers: Vec<Watcher>,
+}
+
+// Each display is written to under its own lock, never the server's, so a
+// slow one holds up the clients whose changes it's sent, not every client.
+type Watcher = Arc<Mutex<TcpStream>>;
+
+impl Server {
+    pub fn new(lanes: usize) -> Server {
+        Server {
+            lanes: vec![Lane::new(); lanes],
+            watchers: vec![],
+        }
+    }
+
+    // The reply to one line, the lane it changed, and whether to close the
+    // connection.
+    fn run(&mut self, line: &str) -> (String, Option<usize>, bool) {
+        let (lane, input) = line.split_once(' ').unwrap_or((line, ""));
+        let input = input.trim();
+        let lanes = 1..=self.lanes.len();
+        let i = lane.parse().ok().filter(|i| lanes.contains(i));
+        let cmd = input.split(' ').next().unwrap_or("");
+        let mut close = false;
+        let (ok, message, changed) = match i {
+            None => (false, "Unknown lane!".to_string(), None),
+            Some(_) if LOCAL.contains(&cmd) => {
+                (false, "Not available over the network!".to_string(), None)
+            }
+            Some(i) => match command(&mut self.lanes[i - 1], input) {
+                Reply::Exit => {
+                    close = true;
+                    (true, "".to_string(), None)
+                }
+                Reply::Clear => (true, "".to_string(), Some(i)),
+                Reply::Redraw(msg) => (true, msg, Some(i)),
+                Reply::Message(msg) => (true, msg, None),
+                Reply::Error(e) => (false, e, None),
+            },
+        };
+        let reply = json::object(&[
+            ("lane", i.map_or("null".to_string(), |i| i.to_string())),
+            ("command", json::string(input)),
+            ("ok", ok.to_string()),
+            ("message", json::string(&message)),
+        ]);
+        (reply, changed, close)
+    }
+
+    fn state(&self, i: usize) -> String {
+        json::object(&[
+            ("lane", i.to_string()),
+            ("state", json::lane(&self.lanes[i - 1])),
+        ])
+    }
+}
+
+// Sends lane `i` to every display, dropping those that have gone.
+fn broadcast(server: &Mutex<Server>, i: usize) {
+    let watchers = server.lock().unwrap().watchers.clone();
+    for watcher in watchers {
+        let mut stream = watcher.lock().unwrap();
+        if !send(server, &mut stream, i) {
+            drop(stream);
+            unwatch(server, &watcher);
+        }
+    }
+}
+
+// Lane `i` is read only once the display's lock is held, so whichever
+// change a display gets last is the latest one.
+fn send(server: &Mutex<Server>, stream: &mut TcpStream, i: usize) -> bool {
+    let state = server.lock().unwrap().state(i) + "\n";
+    stream.write_all(state.as_bytes()).is_ok()
+}
+
+fn unwatch(server: &Mutex<Server>, watcher: &Watcher) {
+    let mut server = server.lock().unwrap();
+    server.watchers.retain(|w| !Arc::ptr_eq(w, watcher));
+}
+
+fn watch(server: &Mutex<Server>, stream: TcpStream) {
+    // A display that stops reading mustn't hold up the lanes for long.
+    let _ = stream.set_write_timeout(Some(Duration::from_secs(1)));
+    let watcher = Arc::new(Mutex::new(stream));
+    // Held while every lane is sent, so changes made meanwhile come after.
+    let mut stream = watcher.lock().unwrap();
+    let lanes = {
+        let mut server = server.lock().unwrap();
+        server.watchers.push(Arc::clone(&watcher));
+        server.lanes.len()
+    };
+    if !(1..=lanes).all(|i| send(server, &mut stream, i)) {
+        drop(stream);
+        unwatch(server, &watcher);
+    }
+}
+
+pub fn serve(listener: TcpListener, lanes: usize) {
+    let server = Arc::new(Mutex::new(Server::new(lanes)));
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let server = Arc::clone(&server);
+        thread::spawn(move || client(&server, stream));
+    }
+}
+
+fn client(server: &Mutex<Server>, stream: TcpStream) {
+    let Ok(mut out) = stream.try_clone() else {
+        return;
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line == "watch" {
+            if let Ok(watcher) = out.try_clone() {
+                watch(server, watcher);
+            }
+            continue;
+        }
+        let (reply, changed, close) = server.lock().unwrap().run(line);
+        if let Some(i) = changed {
+            broadcast(server, i);
+        }
+        if writeln!(out, "{reply}").is_err() {
+            break;
+        }
+        if close {
+            // Also stops any updates, if this client is a display.
+            let _ = out.shutdown(Shutdown::Both);
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::serve::*;
+
+    #[test]
+    fn test_run() {
+        let mut server = Server::new(2);
+        let (reply, changed, _) = server.run("2 add Alice");
+        assert_eq!(
+            r#"{"lane":2,"command":"add Alice","ok":true,"message":""}"#,
+            reply
+        );
+        assert_eq!(Some(2), changed);
+        assert_eq!("Alice", server.lanes[1].bowlers()[0].name());
+        assert_eq!(Some(1), server.run("1 7").1);
+        assert_eq!(Some(1), server.run("1 undo").1);
+        assert_eq!(None, server.run("1 odds").1);
+
+        let (reply, changed, _) = server.run("3 roll 7");
+        assert!(reply.contains(r#""ok":false,"message":"Unknown lane!""#));
+        assert_eq!(None, changed);
+        assert!(server.run("x").0.contains("Unknown lane!"));
+        for cmd in ["1 save x", "2 load x", "1 export x.svg"] {
+            let (reply, _, _) = server.run(cmd);
+            assert!(reply.contains("Not available over the network!"));
+        }
+        let (reply, changed, close) = server.run("1 exit");
+        assert!(reply.contains(r#""ok":true"#));
+        assert_eq!((None, true), (changed, close));
+        assert!(!server.run("1 odds").2);
+    }
+
+    #[test]
+    fn test_serve() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener, 2));
+
+        let display = TcpStream::connect(address).unwrap();
+        let mut updates = BufReader::new(display.try_clone().unwrap()).lines();
+        writeln!(&display, "watch").unwrap();
+        let mut next = || updates.next().unwrap().unwrap();
+        assert!(next().starts_with(r#"{"lane":1,"state":{"#));
+        assert!(next().starts_with(r#"{"lane":2,"state":{"#));
+
+        let tablet = TcpStream::connect(address).unwrap();
+        let mut replies = BufReader::new(tablet.try_clone().unwrap()).lines();
+        writeln!(&tablet, "2 roll 10").unwrap();
+        let reply = replies.next().unwrap().unwrap();
+        assert!(reply.contains(r#""ok":true"#));
+        let update = next();
+        assert!(update.starts_with(r#"{"lane":2,"#));
+        assert!(update.contains(r#""frames":[[10],[]]"#));
+
+        // an error changes nothing, so no update
+        writeln!(&tablet, "1 roll Z").unwrap();
+        writeln!(&tablet, "1 roll 3").unwrap();
+        assert!(replies.next().unwrap().unwrap().contains(r#""ok":false"#));
+        assert!(next().contains(r#""frames":[[3]]"#));
+
+        assert!(replies.next().unwrap().unwrap().contains(r#""ok":true"#));
+
+        // answered, then closed
+        writeln!(&tablet, "1 exit").unwrap();
+        assert!(replies.next().unwrap().unwrap().contains(r#""ok":true"#));
+        assert!(replies.next().is_none());
+        writeln!(&display, "1 exit").unwrap();
+        assert!(next().contains(r#""ok":true"#));
+        assert!(updates.next().is_none());
+    }
+}
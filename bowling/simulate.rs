@@ -0,0 +1,113 @@
+use crate::odds::Model;
+use crate::{Game, Rules, T};
+use rng::Rng;
+
+// Final scores of simulated games, as a count per score.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub histogram: Vec<usize>,
+}
+
+impl Summary {
+    pub fn add(&mut self, score: T) {
+        let i = score as usize;
+        if self.histogram.len() <= i {
+            self.histogram.resize(i + 1, 0);
+        }
+        self.histogram[i] += 1;
+    }
+
+    pub fn games(&self) -> usize {
+        self.histogram.iter().sum()
+    }
+
+    pub fn mean(&self) -> f64 {
+        let scores = self.histogram.iter().enumerate();
+        let total = scores.map(|(s, n)| s * n).sum::<usize>();
+        total as f64 / self.games().max(1) as f64
+    }
+
+    // Lowest score at least `q` of the games didn't beat, for `q` in [0, 1].
+    pub fn percentile(&self, q: f64) -> Option<T> {
+        let rank = ((q * self.games() as f64).ceil() as usize).max(1);
+        let mut seen = 0;
+        for (s, n) in self.histogram.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return Some(s as T);
+            }
+        }
+        None
+    }
+}
+
+// Plays `games` games with every ball drawn from `model`, through the same
+// `Game::roll` as everything else.
+pub fn simulate(
+    rules: &Rules,
+    model: &Model,
+    games: usize,
+    seed: u64,
+) -> Summary {
+    let mut rng = Rng::new(seed);
+    let mut summary = Summary::default();
+    for _ in 0..games {
+        let mut game = Game::new(rules.clone());
+        while !game.is_over() {
+            let p = model.sample(&game, rng.f64());
+            game.roll(&p).unwrap();
+        }
+        summary.add(game.total());
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::odds;
+    use crate::simulate::*;
+
+    #[test]
+    fn test_rng() {
+        // the start of the `rng` tool's stream
+        let mut rng = Rng::new(123);
+        assert_eq!(6487035429886636568, rng.next_u64());
+        assert_eq!(8198376793390951513, rng.next_u64());
+        let mut rng = Rng::new(u64::MAX);
+        assert!((0..1000).all(|_| (0.0..1.0).contains(&rng.f64())));
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut s = Summary::default();
+        for score in [100, 200, 200, 300] {
+            s.add(score);
+        }
+        assert_eq!(4, s.games());
+        assert_eq!(200.0, s.mean());
+        assert_eq!(Some(100), s.percentile(0.0));
+        assert_eq!(Some(100), s.percentile(0.25));
+        assert_eq!(Some(200), s.percentile(0.5));
+        assert_eq!(Some(300), s.percentile(1.0));
+        assert_eq!(None, Summary::default().percentile(0.5));
+    }
+
+    #[test]
+    fn test_simulate() {
+        let rules = Rules::default();
+        let perfect = Model::from_rates(&rules, 1.0, 1.0).unwrap();
+        assert_eq!(
+            vec![10],
+            simulate(&rules, &perfect, 10, 1).histogram[300..]
+        );
+
+        let model = Model::from_rates(&rules, 0.3, 0.5).unwrap();
+        let a = simulate(&rules, &model, 2000, 7);
+        assert_eq!(a, simulate(&rules, &model, 2000, 7));
+        assert_ne!(a, simulate(&rules, &model, 2000, 8));
+
+        // close to the exact mean
+        let exact = odds::mean(&odds::distribution(&Game::default(), &model));
+        assert!((a.mean() - exact).abs() < 3.0, "{} {exact}", a.mean());
+    }
+}
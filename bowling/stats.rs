@@ -0,0 +1,195 @@
+use crate::CompletionStatus::*;
+use crate::{Game, Leave, T};
+use std::collections::BTreeMap;
+
+// Totals over completed games. Strikes, spares and open frames come from
+// `Game::completion_status`, which only tells them apart before the last
+// frame, so the last frame is tracked on its own. Leaves only count frames
+// where they were entered, and splits only on a ten-pin rack.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub games: usize,
+    pub pins: T,
+    pub high: T,
+    pub frames: usize,
+    pub strikes: usize,
+    pub spares: usize,
+    pub opens: usize,
+    pub first_ball_pins: T,
+    pub first_balls: usize,
+    pub tenth_pins: T,
+    pub tenth_marks: usize,
+    pub splits: Conversions,
+    pub leaves: BTreeMap<Leave, Conversions>,
+    pub fouls: usize,
+    pub dead_balls: usize,
+}
+
+// How often a leave was picked up for a spare.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Conversions {
+    pub attempts: usize,
+    pub converted: usize,
+}
+
+impl Conversions {
+    fn add(&mut self, converted: bool) {
+        self.attempts += 1;
+        self.converted += converted as usize;
+    }
+
+    pub fn rate(&self) -> f64 {
+        ratio(self.converted, self.attempts)
+    }
+}
+
+impl Stats {
+    // Incomplete games are ignored.
+    pub fn add(&mut self, game: &Game) {
+        if !game.is_over() {
+            return;
+        }
+        self.games += 1;
+        self.pins += game.total();
+        self.high = self.high.max(game.total());
+        for (i, f) in game.frames().iter().enumerate() {
+            self.first_ball_pins += f.rolls()[0];
+            self.first_balls += 1;
+            self.fouls += f.fouls();
+            self.dead_balls += f.dead_balls().len();
+            if let Some(leave) = f.leave().filter(|l| !l.is_empty()) {
+                let converted = game.mark(i) == Spare;
+                self.leaves.entry(leave).or_default().add(converted);
+                if game.rules().pins() == 10 && leave.is_split() {
+                    self.splits.add(converted);
+                }
+            }
+            if i == game.rules().last_frame() {
+                self.tenth_pins += game.score(i).unwrap();
+                self.tenth_marks += (game.mark(i) != NoBonus) as usize;
+                continue;
+            }
+            self.frames += 1;
+            match game.completion_status(i) {
+                Strike => self.strikes += 1,
+                Spare => self.spares += 1,
+                NoBonus => self.opens += 1,
+                Incomplete => unreachable!(),
+            }
+        }
+    }
+
+    pub fn average(&self) -> f64 {
+        ratio(self.pins as usize, self.games)
+    }
+
+    pub fn strike_rate(&self) -> f64 {
+        ratio(self.strikes, self.frames)
+    }
+
+    // Spares out of the frames that were not strikes.
+    pub fn spare_rate(&self) -> f64 {
+        ratio(self.spares, self.frames - self.strikes)
+    }
+
+    pub fn open_rate(&self) -> f64 {
+        ratio(self.opens, self.frames)
+    }
+
+    pub fn first_ball_average(&self) -> f64 {
+        ratio(self.first_ball_pins as usize, self.first_balls)
+    }
+
+    pub fn tenth_average(&self) -> f64 {
+        ratio(self.tenth_pins as usize, self.games)
+    }
+
+    pub fn tenth_mark_rate(&self) -> f64 {
+        ratio(self.tenth_marks, self.games)
+    }
+}
+
+fn ratio(a: usize, b: usize) -> f64 {
+    if b == 0 {
+        0.0
+    } else {
+        a as f64 / b as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stats::*;
+    use crate::Delivery;
+
+    fn stats(games: &[&str]) -> Stats {
+        let mut stats = Stats::default();
+        for g in games {
+            stats.add(&g.parse().unwrap());
+        }
+        stats
+    }
+
+    #[test]
+    fn test_leaves() {
+        let mut g = Game::default();
+        let leave = |s: &str| s.parse::<Leave>().unwrap();
+        for _ in 0..5 {
+            g.roll_leave(&8, &leave("7-10")).unwrap();
+            g.roll(&1).unwrap();
+            g.roll_leave(&9, &leave("10")).unwrap();
+            g.roll(&1).unwrap();
+        }
+        g.roll(&10).unwrap();
+        let mut s = Stats::default();
+        s.add(&g);
+        let conversions = |attempts, converted| Conversions {
+            attempts,
+            converted,
+        };
+        assert_eq!(conversions(5, 0), s.splits);
+        assert_eq!(Some(&conversions(5, 0)), s.leaves.get(&leave("7-10")));
+        assert_eq!(Some(&conversions(5, 5)), s.leaves.get(&leave("10")));
+        assert_eq!(0.0, s.splits.rate());
+        assert_eq!(4.0 / 9.0, s.spare_rate());
+    }
+
+    #[test]
+    fn test_stats() {
+        let s = stats(&["XXXXXXXXXXXX", "9/9/9/9/9/9/9/9/9/9/9", "X"]);
+        assert_eq!(2, s.games);
+        assert_eq!(300, s.high);
+        assert_eq!(245.0, s.average());
+        assert_eq!(0.5, s.strike_rate());
+        assert_eq!(1.0, s.spare_rate());
+        assert_eq!(0.0, s.open_rate());
+        assert_eq!(9.5, s.first_ball_average());
+        assert_eq!(24.5, s.tenth_average());
+        assert_eq!(1.0, s.tenth_mark_rate());
+
+        let s = stats(&["X 7/ 9- 81 X X X 9/ -- 9-"]);
+        assert_eq!(4.0 / 9.0, s.strike_rate());
+        assert_eq!(2.0 / 5.0, s.spare_rate());
+        assert_eq!(3.0 / 9.0, s.open_rate());
+        assert_eq!(9.0, s.tenth_average());
+        assert_eq!(0.0, s.tenth_mark_rate());
+        assert_eq!(Stats::default(), stats(&[]));
+        assert!(s.leaves.is_empty());
+        assert_eq!(0.0, Stats::default().average());
+    }
+
+    #[test]
+    fn test_fouls() {
+        let mut g = "F/ 9F".parse::<Game>().unwrap();
+        g.deliver(&Delivery::DeadBall).unwrap();
+        for _ in 0..16 {
+            g.roll(&0).unwrap();
+        }
+        let mut s = Stats::default();
+        s.add(&g);
+        assert_eq!((2, 1), (s.fouls, s.dead_balls));
+        // a first ball foul still counts as a first ball
+        assert_eq!(0.9, s.first_ball_average());
+        assert_eq!(1.0 / 9.0, s.spare_rate());
+    }
+}
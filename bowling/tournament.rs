@@ -0,0 +1,587 @@
+use crate::lane::Lane;
+use crate::{Game, T};
+use std::{fmt, str::FromStr};
+
+// Entrants in seed order, best first. Match play is a single elimination
+// bracket with byes for the top seeds; a stepladder starts with the two
+// lowest seeds, and each winner moves up to meet the next seed. A tied game
+// is rolled off: a match goes to whoever won the last game between them.
+// An entrant can be a team bowling Baker games. Saved as:
+//
+//     format stepladder
+//     entrant Alice
+//     entrant Pin Pals: Ann, Bo, Cy, Di, Ed
+//     game 190 210 Alice vs Pin Pals
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TournamentError {
+    UnknownFormat(String),
+    TooFewEntrants,
+    DuplicateEntrant(String),
+    UnknownEntrant(String),
+    NoSuchMatch(String, String),
+    NotAMatch,
+    Unfinished(String),
+    InvalidName(String),
+}
+
+impl fmt::Display for TournamentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TournamentError::UnknownFormat(s) => {
+                write!(f, "Unknown format! ({s})")
+            }
+            TournamentError::TooFewEntrants => write!(f, "Too few entrants!"),
+            TournamentError::DuplicateEntrant(s) => {
+                write!(f, "Duplicate entrant! ({s})")
+            }
+            TournamentError::UnknownEntrant(s) => {
+                write!(f, "Unknown entrant! ({s})")
+            }
+            TournamentError::NoSuchMatch(a, b) => {
+                write!(f, "No match to bowl! ({a} vs {b})")
+            }
+            TournamentError::NotAMatch => {
+                write!(f, "A match needs exactly two bowlers!")
+            }
+            TournamentError::Unfinished(s) => {
+                write!(f, "Unfinished game! ({s})")
+            }
+            TournamentError::InvalidName(s) => {
+                write!(f, "Invalid name! ({s})")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TournamentError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    InvalidLine(usize),
+    Tournament(usize, TournamentError),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::InvalidLine(i) => write!(f, "line {i}: Invalid line!"),
+            LoadError::Tournament(i, e) => write!(f, "line {i}: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    MatchPlay,
+    Stepladder,
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Format::MatchPlay => write!(f, "match-play"),
+            Format::Stepladder => write!(f, "stepladder"),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = TournamentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "match-play" => Ok(Format::MatchPlay),
+            "stepladder" => Ok(Format::Stepladder),
+            _ => Err(TournamentError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+// A place in a round: an entrant, or a bye, or the winner of a match that
+// hasn't been decided yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    Bye,
+    Waiting,
+    Entrant(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entrant {
+    pub name: String,
+    // Baker order, empty for a single bowler.
+    pub members: Vec<String>,
+}
+
+// One game between two entrants, by seed index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Played {
+    pub a: usize,
+    pub b: usize,
+    pub a_score: T,
+    pub b_score: T,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tournament {
+    format: Format,
+    entrants: Vec<Entrant>,
+    games: Vec<Played>,
+}
+
+impl Tournament {
+    pub fn new(
+        format: Format,
+        entrants: Vec<Entrant>,
+    ) -> Result<Tournament, TournamentError> {
+        if entrants.len() < 2 {
+            return Err(TournamentError::TooFewEntrants);
+        }
+        for (i, e) in entrants.iter().enumerate() {
+            if !valid_name(&e.name) {
+                return Err(TournamentError::InvalidName(e.name.clone()));
+            }
+            // Members are listed with commas between them.
+            let mut members = e.members.iter();
+            if let Some(m) = members.find(|m| !valid_name(m) || m.contains(','))
+            {
+                return Err(TournamentError::InvalidName(m.clone()));
+            }
+            if entrants[..i].iter().any(|x| x.name == e.name) {
+                return Err(TournamentError::DuplicateEntrant(e.name.clone()));
+            }
+        }
+        Ok(Tournament {
+            format,
+            entrants,
+            games: vec![],
+        })
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn entrants(&self) -> &[Entrant] {
+        &self.entrants
+    }
+
+    pub fn games(&self) -> &[Played] {
+        &self.games
+    }
+
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.entrants.iter().position(|e| e.name == name)
+    }
+
+    // Whoever won the last game between `a` and `b`, if it wasn't a tie.
+    pub fn match_winner(&self, a: usize, b: usize) -> Option<usize> {
+        let last = self
+            .games
+            .iter()
+            .rev()
+            .find(|g| (g.a, g.b) == (a, b) || (g.a, g.b) == (b, a))?;
+        match last.a_score.cmp(&last.b_score) {
+            std::cmp::Ordering::Greater => Some(last.a),
+            std::cmp::Ordering::Less => Some(last.b),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    fn advance(&self, a: Slot, b: Slot) -> Slot {
+        match (a, b) {
+            (Slot::Bye, x) | (x, Slot::Bye) => x,
+            (Slot::Entrant(a), Slot::Entrant(b)) => {
+                self.match_winner(a, b).map_or(Slot::Waiting, Slot::Entrant)
+            }
+            _ => Slot::Waiting,
+        }
+    }
+
+    // Every round's pairings so far, first round first.
+    pub fn rounds(&self) -> Vec<Vec<(Slot, Slot)>> {
+        let n = self.entrants.len();
+        let mut rounds = vec![];
+        match self.format {
+            Format::MatchPlay => {
+                let mut slots = seeding(n.next_power_of_two())
+                    .into_iter()
+                    .map(|s| if s < n { Slot::Entrant(s) } else { Slot::Bye })
+                    .collect::<Vec<_>>();
+                while slots.len() > 1 {
+                    let pairs = slots
+                        .chunks(2)
+                        .map(|c| (c[0], c[1]))
+                        .collect::<Vec<_>>();
+                    let winners =
+                        pairs.iter().map(|&(a, b)| self.advance(a, b));
+                    slots = winners.collect();
+                    rounds.push(pairs);
+                }
+            }
+            Format::Stepladder => {
+                let mut up = Slot::Entrant(n - 1);
+                for seed in (0..n - 1).rev() {
+                    let pair = (up, Slot::Entrant(seed));
+                    up = self.advance(pair.0, pair.1);
+                    rounds.push(vec![pair]);
+                }
+            }
+        }
+        rounds
+    }
+
+    pub fn winner(&self) -> Option<usize> {
+        let rounds = self.rounds();
+        let &(a, b) = rounds.last()?.first()?;
+        match self.advance(a, b) {
+            Slot::Entrant(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    // Matches that can be bowled now.
+    pub fn pending(&self) -> Vec<(usize, usize)> {
+        let pairs = self.rounds().into_iter().flatten();
+        pairs
+            .filter_map(|pair| match pair {
+                (Slot::Entrant(a), Slot::Entrant(b)) => Some((a, b)),
+                _ => None,
+            })
+            .filter(|&(a, b)| self.match_winner(a, b).is_none())
+            .collect()
+    }
+
+    pub fn record(
+        &mut self,
+        a: &str,
+        a_score: T,
+        b: &str,
+        b_score: T,
+    ) -> Result<(), TournamentError> {
+        let find = |name: &str| {
+            self.position(name)
+                .ok_or_else(|| TournamentError::UnknownEntrant(name.into()))
+        };
+        let (i, j) = (find(a)?, find(b)?);
+        let pending = self.pending();
+        if !pending.contains(&(i, j)) && !pending.contains(&(j, i)) {
+            return Err(TournamentError::NoSuchMatch(a.into(), b.into()));
+        }
+        self.games.push(Played {
+            a: i,
+            b: j,
+            a_score,
+            b_score,
+        });
+        Ok(())
+    }
+
+    // A finished lane of the two entrants in a pending match.
+    pub fn record_lane(&mut self, lane: &Lane) -> Result<(), TournamentError> {
+        let [a, b] = lane.bowlers() else {
+            return Err(TournamentError::NotAMatch);
+        };
+        for x in [a, b] {
+            if !x.game().is_over() {
+                return Err(TournamentError::Unfinished(x.name().to_string()));
+            }
+        }
+        let (a_score, b_score) = (a.game().total(), b.game().total());
+        self.record(a.name(), a_score, b.name(), b_score)
+    }
+}
+
+// Bracket order of 0-based seeds for `size` places, a power of two, so the
+// top seeds only meet in the late rounds: 0 7 3 4 1 6 2 5 for 8.
+fn seeding(size: usize) -> Vec<usize> {
+    let mut order = vec![0];
+    while order.len() < size {
+        let n = order.len() * 2;
+        order = order.iter().flat_map(|&s| [s, n - 1 - s]).collect();
+    }
+    order
+}
+
+// A name `load` reads back as it was: a team's members follow a `:` and
+// games separate the two names with ` vs `.
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.trim() == name
+        && !name.contains([':', '\n', '\r'])
+        && !name.contains(" vs ")
+}
+
+// Each member's frames of a Baker game: each takes a frame in turn, so five
+// members bowl two frames each of a ten-pin game.
+pub fn baker_frames(members: &[String], game: &Game) -> Vec<Vec<usize>> {
+    let mut frames = vec![vec![]; members.len()];
+    for i in 0..game.frames().len() {
+        if let Some(m) = frames.get_mut(i % members.len().max(1)) {
+            m.push(i);
+        }
+    }
+    frames
+}
+
+pub fn save(t: &Tournament) -> String {
+    let mut s = format!("format {}\n", t.format);
+    for e in &t.entrants {
+        match e.members.is_empty() {
+            true => s += &format!("entrant {}\n", e.name),
+            false => {
+                s += &format!("entrant {}: {}\n", e.name, e.members.join(", "))
+            }
+        }
+    }
+    for g in &t.games {
+        let (a, b) = (&t.entrants[g.a].name, &t.entrants[g.b].name);
+        s += &format!("game {} {} {a} vs {b}\n", g.a_score, g.b_score);
+    }
+    s
+}
+
+pub fn load(s: &str) -> Result<Tournament, LoadError> {
+    let lines = s.lines().map(|l| l.trim_end_matches('\r'));
+    let mut format = Format::default();
+    let mut entrants = vec![];
+    let mut games = vec![];
+    let mut start = 0;
+    for (i, line) in lines.enumerate().filter(|(_, l)| !l.is_empty()) {
+        let i = i + 1;
+        let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match kind {
+            "format" if entrants.is_empty() => {
+                format =
+                    rest.parse().map_err(|e| LoadError::Tournament(i, e))?
+            }
+            "entrant" if games.is_empty() => {
+                let (name, members) =
+                    rest.split_once(':').unwrap_or((rest, ""));
+                let members = members
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|m| !m.is_empty())
+                    .map(|m| m.to_string());
+                entrants.push(Entrant {
+                    name: name.trim().to_string(),
+                    members: members.collect(),
+                });
+                start = i;
+            }
+            "game" => {
+                let mut words = rest.splitn(3, ' ');
+                let mut score = || {
+                    let score = words.next().and_then(|w| w.parse().ok());
+                    score.ok_or(LoadError::InvalidLine(i))
+                };
+                let (a_score, b_score) = (score()?, score()?);
+                let names = words.next().and_then(|w| w.split_once(" vs "));
+                let (a, b) = names.ok_or(LoadError::InvalidLine(i))?;
+                games.push((i, a, a_score, b, b_score));
+            }
+            _ => return Err(LoadError::InvalidLine(i)),
+        }
+    }
+    let mut t = Tournament::new(format, entrants)
+        .map_err(|e| LoadError::Tournament(start, e))?;
+    for (i, a, a_score, b, b_score) in games {
+        t.record(a, a_score, b, b_score)
+            .map_err(|e| LoadError::Tournament(i, e))?;
+    }
+    Ok(t)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tournament::*;
+
+    fn tournament(format: Format, n: usize) -> Tournament {
+        let entrants = (1..=n).map(|i| Entrant {
+            name: format!("s{i}"),
+            members: vec![],
+        });
+        Tournament::new(format, entrants.collect()).unwrap()
+    }
+
+    #[test]
+    fn test_seeding() {
+        assert_eq!(vec![0, 7, 3, 4, 1, 6, 2, 5], seeding(8));
+        assert_eq!(vec![0], seeding(1));
+    }
+
+    #[test]
+    fn test_match_play() {
+        let mut t = tournament(Format::MatchPlay, 5);
+        // the top three seeds get byes, so the second and third can go
+        // straight on to the second round
+        assert_eq!(vec![(3, 4), (1, 2)], t.pending());
+        assert_eq!(
+            Err(TournamentError::NoSuchMatch("s1".into(), "s2".into())),
+            t.record("s1", 200, "s2", 100)
+        );
+        t.record("s5", 180, "s4", 170).unwrap();
+        assert_eq!(vec![(0, 4), (1, 2)], t.pending());
+        t.record("s1", 150, "s5", 150).unwrap();
+        // a tie is rolled off
+        assert_eq!(vec![(0, 4), (1, 2)], t.pending());
+        t.record("s5", 30, "s1", 20).unwrap();
+        t.record("s2", 190, "s3", 210).unwrap();
+        assert_eq!(None, t.winner());
+        assert_eq!(vec![(4, 2)], t.pending());
+        t.record("s3", 300, "s5", 299).unwrap();
+        assert_eq!(Some(2), t.winner());
+        assert!(t.pending().is_empty());
+        assert_eq!(3, t.rounds().len());
+        assert_eq!(
+            vec![
+                (Slot::Entrant(0), Slot::Bye),
+                (Slot::Entrant(3), Slot::Entrant(4)),
+                (Slot::Entrant(1), Slot::Bye),
+                (Slot::Entrant(2), Slot::Bye),
+            ],
+            t.rounds()[0]
+        );
+
+        let t = tournament(Format::MatchPlay, 2);
+        assert_eq!(vec![(0, 1)], t.pending());
+    }
+
+    #[test]
+    fn test_stepladder() {
+        let mut t = tournament(Format::Stepladder, 4);
+        assert_eq!(vec![(3, 2)], t.pending());
+        t.record("s4", 220, "s3", 200).unwrap();
+        assert_eq!(vec![(3, 1)], t.pending());
+        t.record("s4", 180, "s2", 190).unwrap();
+        t.record("s2", 201, "s1", 200).unwrap();
+        assert_eq!(Some(1), t.winner());
+        assert_eq!(
+            vec![
+                vec![(Slot::Entrant(3), Slot::Entrant(2))],
+                vec![(Slot::Entrant(3), Slot::Entrant(1))],
+                vec![(Slot::Entrant(1), Slot::Entrant(0))],
+            ],
+            t.rounds()
+        );
+    }
+
+    #[test]
+    fn test_lane() {
+        let mut t = tournament(Format::Stepladder, 3);
+        let mut lane =
+            Lane::with_bowlers(&["s3", "s2"], Default::default()).unwrap();
+        for _ in 0..12 {
+            lane.roll_bowler(0, &10).unwrap();
+        }
+        assert_eq!(
+            Err(TournamentError::Unfinished("s2".into())),
+            t.record_lane(&lane)
+        );
+        for _ in 0..20 {
+            lane.roll_bowler(1, &4).unwrap();
+        }
+        t.record_lane(&lane).unwrap();
+        assert_eq!(
+            Played {
+                a: 2,
+                b: 1,
+                a_score: 300,
+                b_score: 80
+            },
+            t.games()[0]
+        );
+        assert_eq!(
+            Err(TournamentError::NotAMatch),
+            t.record_lane(&Lane::new())
+        );
+    }
+
+    #[test]
+    fn test_baker() {
+        let members = ["a", "b", "c", "d", "e"].map(String::from);
+        let game = "X X X".parse::<Game>().unwrap();
+        // three strikes and the fourth frame just started
+        assert_eq!(
+            vec![vec![0], vec![1], vec![2], vec![3], vec![]],
+            baker_frames(&members, &game)
+        );
+        assert_eq!(Vec::<Vec<usize>>::new(), baker_frames(&[], &game));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut t = Tournament::new(
+            Format::Stepladder,
+            vec![
+                Entrant {
+                    name: "Alice".into(),
+                    members: vec![],
+                },
+                Entrant {
+                    name: "Pin Pals".into(),
+                    members: vec!["Ann".into(), "Bo".into()],
+                },
+            ],
+        )
+        .unwrap();
+        t.record("Alice", 190, "Pin Pals", 210).unwrap();
+        let s = save(&t);
+        assert_eq!(
+            "format stepladder\nentrant Alice\nentrant Pin Pals: Ann, Bo\n\
+             game 190 210 Alice vs Pin Pals\n",
+            s
+        );
+        assert_eq!(Ok(t), load(&s));
+        assert_eq!(
+            Err(LoadError::Tournament(1, TournamentError::TooFewEntrants)),
+            load("entrant a")
+        );
+        assert_eq!(
+            Err(LoadError::Tournament(
+                4,
+                TournamentError::NoSuchMatch("a".into(), "c".into())
+            )),
+            load("entrant a\nentrant b\nentrant c\ngame 1 2 a vs c")
+        );
+        assert_eq!(
+            Err(LoadError::InvalidLine(3)),
+            load("entrant a\nentrant b\ngame 1 a vs b")
+        );
+        assert_eq!(
+            Err(LoadError::Tournament(
+                1,
+                TournamentError::UnknownFormat("x".into())
+            )),
+            load("format x")
+        );
+    }
+
+    #[test]
+    fn test_names() {
+        let entrant = |name: &str, members: &[&str]| Entrant {
+            name: name.into(),
+            members: members.iter().map(|&m| m.into()).collect(),
+        };
+        let new =
+            |e| Tournament::new(Format::Stepladder, vec![entrant("a", &[]), e]);
+        for bad in ["b: c", "b vs c", "", " b", "b\nentrant c"] {
+            assert_eq!(
+                Err(TournamentError::InvalidName(bad.into())),
+                new(entrant(bad, &[]))
+            );
+        }
+        for bad in ["c, d", "c: d", "c vs d", ""] {
+            assert_eq!(
+                Err(TournamentError::InvalidName(bad.into())),
+                new(entrant("b", &["c", bad]))
+            );
+        }
+        let t = new(entrant("Strikers, Inc", &["Jo Vs"])).unwrap();
+        assert_eq!(Ok(t.clone()), load(&save(&t)));
+    }
+}
@@ -0,0 +1,395 @@
+use crate::{autosave, command, line_mode, Reply};
+use bowling::lane::Lane;
+use bowling::{notation, Game};
+use std::io::{self, IsTerminal, Read, Write};
+
+pub const CLEAR: &str = "\x1b[2J\x1b[H";
+const ENTER: &str = "\x1b[?1049h\x1b[?25l";
+const LEAVE: &str = "\x1b[?25h\x1b[?1049l";
+const HOME: &str = "\x1b[H";
+const CLEAR_LINE: &str = "\x1b[K";
+const CLEAR_BELOW: &str = "\x1b[J";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const REVERSE: &str = "\x1b[7m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const STRIKE: &str = "\x1b[1;33m";
+const SPARE: &str = "\x1b[1;36m";
+// Back to plain text, without undoing reverse video.
+const PLAIN: &str = "\x1b[22;39m";
+const RESET: &str = "\x1b[0m";
+
+const HELP: &str =
+    "0-9 X / - F roll   d dead ball   u undo   r redo   : command   q quit";
+
+pub fn available() -> bool {
+    cfg!(unix) && io::stdin().is_terminal() && io::stdout().is_terminal()
+}
+
+// A full screen scoresheet, redrawn in place after every key. Falls back to
+// line mode if the terminal can't be put in raw mode.
+pub fn run(lane: Lane, note: String) {
+    let Some(raw) = RawMode::enable() else {
+        return line_mode(lane, note);
+    };
+    let mut ui = Ui {
+        lane,
+        message: note,
+        error: false,
+        input: None,
+    };
+    print!("{ENTER}");
+    let mut keys = io::stdin().lock().bytes();
+    loop {
+        ui.draw();
+        let Some(Ok(key)) = keys.next() else {
+            break;
+        };
+        if !ui.key(key) {
+            break;
+        }
+    }
+    print!("{LEAVE}");
+    io::stdout().flush().unwrap();
+    drop(raw);
+}
+
+struct Ui {
+    lane: Lane,
+    message: String,
+    error: bool,
+    // The command being typed after `:`.
+    input: Option<Vec<u8>>,
+}
+
+impl Ui {
+    // Returns false to quit.
+    fn key(&mut self, key: u8) -> bool {
+        if let Some(input) = &mut self.input {
+            match key {
+                b'\r' | b'\n' => {
+                    let line =
+                        String::from_utf8_lossy(input).trim().to_string();
+                    self.input = None;
+                    return self.run(&line);
+                }
+                // escape
+                27 => self.input = None,
+                // backspace
+                8 | 127 => {
+                    input.pop();
+                }
+                _ if key >= b' ' => input.push(key),
+                _ => {}
+            }
+            return true;
+        }
+        match key {
+            // ctrl-c, ctrl-d
+            b'q' | 3 | 4 => false,
+            b'u' => self.run("undo"),
+            b'r' => self.run("redo"),
+            b'd' => self.run("dead"),
+            b':' => {
+                self.input = Some(vec![]);
+                true
+            }
+            b'0'..=b'9' | b'x' | b'X' | b'/' | b'-' | b'f' | b'F' => {
+                self.run(&(key as char).to_string())
+            }
+            _ => true,
+        }
+    }
+
+    fn run(&mut self, input: &str) -> bool {
+        let (mut message, mut error) = match command(&mut self.lane, input) {
+            Reply::Exit => return false,
+            Reply::Clear => ("".to_string(), false),
+            Reply::Redraw(note) => (note, false),
+            Reply::Message(msg) => (msg, false),
+            Reply::Error(e) => (e, true),
+        };
+        if !error {
+            if let Err(e) = autosave(&self.lane) {
+                message = format!("Autosave failed: {e}");
+                error = true;
+            }
+        }
+        self.message = message;
+        self.error = error;
+        true
+    }
+
+    fn draw(&self) {
+        let mut lines = vec![];
+        let rules = self.lane.rules();
+        if rules == &Default::default() {
+            lines.push("Bowling".to_string());
+        } else {
+            lines.push(format!("Bowling  {DIM}{rules}{RESET}"));
+        }
+        lines.push("".to_string());
+
+        let turn = self.lane.turn();
+        for (i, b) in self.lane.bowlers().iter().enumerate() {
+            let marker = if turn == Some(i) { ">" } else { " " };
+            if !b.name().is_empty() {
+                lines.push(format!("{marker} {}", b.name()));
+            }
+            let current =
+                (turn == Some(i)).then(|| b.game().frames().len() - 1);
+            lines.extend(scoresheet(b.game(), current));
+            if !b.game().is_over() {
+                let (max, min) = b.game().projection();
+                lines.push(format!("{DIM}  max {max}  min {min}{RESET}"));
+            }
+            lines.push("".to_string());
+        }
+
+        lines.push(format!("{DIM}{HELP}{RESET}"));
+        for line in self.message.lines() {
+            match self.error {
+                true => lines.push(format!("{RED}{line}{RESET}")),
+                false => lines.push(line.to_string()),
+            }
+        }
+        let cursor = match &self.input {
+            Some(input) => {
+                lines.push(format!(":{}", String::from_utf8_lossy(input)));
+                SHOW_CURSOR
+            }
+            None => HIDE_CURSOR,
+        };
+
+        let mut screen = HOME.to_string();
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                screen += "\r\n";
+            }
+            screen += line;
+            screen += CLEAR_LINE;
+        }
+        screen += CLEAR_BELOW;
+        screen += cursor;
+        let mut out = io::stdout().lock();
+        out.write_all(screen.as_bytes()).unwrap();
+        out.flush().unwrap();
+    }
+}
+
+// The frames and running totals rows of `Game::print`, with marks coloured
+// and the `current` frame highlighted.
+fn scoresheet(game: &Game, current: Option<usize>) -> [String; 2] {
+    let last = game.rules().last_frame();
+    let mut frames = String::new();
+    let mut totals = String::new();
+    for i in 0..=last {
+        let marks = game.frame_to_string(i);
+        let (marks, total) = if i < last {
+            (format!("{marks:^3}"), format!("{:>3}", total(game, i)))
+        } else {
+            (format!("{marks:5}"), format!("{:>5}", total(game, i)))
+        };
+        let marks = paint(&marks);
+        let marks = match current == Some(i) {
+            true => format!("{REVERSE}{marks}{RESET}"),
+            false => marks,
+        };
+        let gap = if i < last { "  " } else { "" };
+        frames += &format!("{}:[{marks}]{gap}", i + 1);
+        let pad = " ".repeat((i + 1).to_string().len());
+        totals += &format!("{pad} [{total}]{gap}");
+    }
+    [frames, totals]
+}
+
+fn total(game: &Game, i: usize) -> String {
+    match game.running_total(i) {
+        Some(total) => total.to_string(),
+        None => "".to_string(),
+    }
+}
+
+fn paint(marks: &str) -> String {
+    let mut s = String::new();
+    for c in marks.chars() {
+        match c {
+            notation::STRIKE => s += &format!("{STRIKE}{c}{PLAIN}"),
+            notation::SPARE => s += &format!("{SPARE}{c}{PLAIN}"),
+            _ => s.push(c),
+        }
+    }
+    s
+}
+
+// Raw mode for as long as this lives, restoring the terminal on drop.
+#[cfg(unix)]
+struct RawMode(sys::Termios);
+
+#[cfg(unix)]
+impl RawMode {
+    fn enable() -> Option<RawMode> {
+        let saved = sys::get()?;
+        let mut raw = saved;
+        sys::make_raw(&mut raw);
+        sys::set(&raw)?;
+        Some(RawMode(saved))
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        sys::set(&self.0);
+    }
+}
+
+#[cfg(not(unix))]
+struct RawMode;
+
+#[cfg(not(unix))]
+impl RawMode {
+    fn enable() -> Option<RawMode> {
+        None
+    }
+}
+
+// termios through libc, which std already links, laid out as each
+// platform's headers declare it. On a unix the layout isn't known for,
+// `get` fails and the scoresheet falls back to line mode.
+#[cfg(unix)]
+mod sys {
+    use std::ffi::{self as c, c_int};
+    use std::mem::MaybeUninit;
+
+    // glibc and musl, with NCCS of 32. Each layout is checked against the
+    // size of `struct termios` in that platform's headers.
+    #[cfg(all(
+        target_os = "linux",
+        any(
+            target_arch = "x86",
+            target_arch = "x86_64",
+            target_arch = "arm",
+            target_arch = "aarch64",
+            target_arch = "riscv64"
+        )
+    ))]
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct Termios {
+        flags: [c::c_uint; 4],
+        line: u8,
+        cc: [u8; 32],
+        speeds: [c::c_uint; 2],
+    }
+
+    #[cfg(all(
+        target_os = "linux",
+        any(
+            target_arch = "x86",
+            target_arch = "x86_64",
+            target_arch = "arm",
+            target_arch = "aarch64",
+            target_arch = "riscv64"
+        )
+    ))]
+    const _: () = assert!(std::mem::size_of::<Termios>() == 60);
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct Termios {
+        flags: [c::c_ulong; 4],
+        cc: [u8; 20],
+        speeds: [c::c_ulong; 2],
+    }
+
+    #[cfg(all(
+        any(target_os = "macos", target_os = "ios"),
+        target_pointer_width = "64"
+    ))]
+    const _: () = assert!(std::mem::size_of::<Termios>() == 72);
+
+    #[cfg(any(
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct Termios {
+        flags: [c::c_uint; 4],
+        cc: [u8; 20],
+        speeds: [c::c_int; 2],
+    }
+
+    #[cfg(any(
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    const _: () = assert!(std::mem::size_of::<Termios>() == 44);
+
+    #[cfg(not(any(
+        all(
+            target_os = "linux",
+            any(
+                target_arch = "x86",
+                target_arch = "x86_64",
+                target_arch = "arm",
+                target_arch = "aarch64",
+                target_arch = "riscv64"
+            )
+        ),
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )))]
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct Termios {
+        unknown: u8,
+    }
+
+    // Whether `Termios` above is the real one, which libc can be given.
+    const KNOWN: bool = std::mem::size_of::<Termios>() > 1;
+
+    const STDIN: c_int = 0;
+    const TCSANOW: c_int = 0;
+
+    extern "C" {
+        fn tcgetattr(fd: c_int, termios: *mut Termios) -> c_int;
+        fn tcsetattr(
+            fd: c_int,
+            action: c_int,
+            termios: *const Termios,
+        ) -> c_int;
+        fn cfmakeraw(termios: *mut Termios);
+    }
+
+    pub fn get() -> Option<Termios> {
+        if !KNOWN {
+            return None;
+        }
+        let mut t = MaybeUninit::uninit();
+        let ok = unsafe { tcgetattr(STDIN, t.as_mut_ptr()) } == 0;
+        ok.then(|| unsafe { t.assume_init() })
+    }
+
+    pub fn set(t: &Termios) -> Option<()> {
+        let ok = KNOWN && unsafe { tcsetattr(STDIN, TCSANOW, t) } == 0;
+        ok.then_some(())
+    }
+
+    pub fn make_raw(t: &mut Termios) {
+        if KNOWN {
+            unsafe { cfmakeraw(t) }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::ops::Range;
+
+const P0: u64 = 0xa0761d6478bd642f;
+const P1: u128 = 0xe7037ed1a0b428db;
+
+// wyrand: small, fast and not for anything secret. The same seed always
+// gives the same numbers, which is what the test input relies on.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    seed: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.seed = self.seed.wrapping_add(P0);
+        let b = self.seed as u128;
+        let c = (b ^ P1) * b;
+        ((c >> 64) ^ c) as u64
+    }
+
+    // The high bits, which are the better mixed.
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    // Uniform over `range`, without the bias of `x % n`. Lemire's method:
+    // the high half of `x * n` is the pick, and the few `x` that would
+    // favour some picks are drawn again.
+    pub fn range(&mut self, range: Range<u64>) -> u64 {
+        assert!(range.start < range.end, "empty range");
+        let n = range.end - range.start;
+        let mut m = self.next_u64() as u128 * n as u128;
+        if (m as u64) < n {
+            let t = n.wrapping_neg() % n;
+            while (m as u64) < t {
+                m = self.next_u64() as u128 * n as u128;
+            }
+        }
+        range.start + (m >> 64) as u64
+    }
+
+    // Uniform in [0, 1).
+    pub fn f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn bool(&mut self) -> bool {
+        self.next_u64() >> 63 == 1
+    }
+
+    pub fn fill_bytes(&mut self, bytes: &mut [u8]) {
+        for chunk in bytes.chunks_mut(8) {
+            let x = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&x[..chunk.len()]);
+        }
+    }
+}
+
+impl Iterator for Rng {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_u64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_next_u64() {
+        let mut rng = Rng::new(123);
+        let first = [rng.next_u64(), rng.next_u64()];
+        assert_eq!(first.to_vec(), Rng::new(123).take(2).collect::<Vec<_>>());
+        assert_ne!(first[0], first[1]);
+        // the seed wraps instead of overflowing
+        let mut rng = Rng::new(u64::MAX);
+        rng.next_u64();
+        assert_eq!(u64::MAX.wrapping_add(P0), rng.seed);
+    }
+
+    #[test]
+    fn test_range() {
+        let mut rng = Rng::new(1);
+        let mut counts = [0; 12];
+        for _ in 0..120_000 {
+            let x = rng.range(3..15);
+            assert!((3..15).contains(&x));
+            counts[x as usize - 3] += 1;
+        }
+        assert!(counts.iter().all(|&n| (9_500..10_500).contains(&n)));
+        assert_eq!(7, rng.range(7..8));
+        rng.range(0..u64::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "empty range")]
+    fn test_empty_range() {
+        Rng::new(1).range(5..5);
+    }
+
+    #[test]
+    fn test_f64_bool() {
+        let mut rng = Rng::new(2);
+        let xs = (0..10_000).map(|_| rng.f64()).collect::<Vec<_>>();
+        assert!(xs.iter().all(|x| (0.0..1.0).contains(x)));
+        let mean = xs.iter().sum::<f64>() / xs.len() as f64;
+        assert!((mean - 0.5).abs() < 0.02);
+        let heads = (0..10_000).filter(|_| rng.bool()).count();
+        assert!((4_800..5_200).contains(&heads));
+    }
+
+    #[test]
+    fn test_fill_bytes() {
+        let mut bytes = [0; 11];
+        Rng::new(3).fill_bytes(&mut bytes);
+        let mut rng = Rng::new(3);
+        let a = rng.next_u64().to_le_bytes();
+        let b = rng.next_u64().to_le_bytes();
+        assert_eq!(a, bytes[..8]);
+        assert_eq!(b[..3], bytes[8..]);
+    }
+}